@@ -36,7 +36,7 @@ fn do_parse(name: &str, path: &str) {
 
     Python::with_gil(|py| {
         let translate_start = Instant::now();
-        let _mod_py = module_to_py(py, project.root_ob).unwrap();
+        let _mod_py = module_to_py(py, project.root_ob, false).unwrap();
         let translate_end = Instant::now();
         println!(
             "  Translate => {}ms",
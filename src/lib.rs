@@ -11,9 +11,34 @@ fn parse_py(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<object::py::AltObject>()?;
     m.add_class::<object::py::Module>()?;
     m.add_class::<object::py::Class>()?;
+    m.add_class::<object::py::Variable>()?;
     m.add_class::<object::py::FormalParamKind>()?;
     m.add_class::<object::py::FormalParam>()?;
     m.add_class::<object::py::Function>()?;
+    m.add_class::<project::py::ParseOptions>()?;
     m.add_function(wrap_pyfunction!(project::py::module_from_dir, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::modules_from_dirs, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::module_from_manifest, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::parse_project, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::module_outline, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::duplicate_functions, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::subclasses_of, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::test_functions, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::import_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::circular_imports, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::project_dot, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::objects_in_file, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::module_subtree, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::symbol_table, m)?)?;
+    #[cfg(feature = "serde")]
+    m.add_function(wrap_pyfunction!(project::py::project_json, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::project_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::total_statements, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::list_files, m)?)?;
+    m.add_function(wrap_pyfunction!(project::py::detect_encodings, m)?)?;
+    m.add_function(wrap_pyfunction!(object::py::parse_expr, m)?)?;
+    m.add_function(wrap_pyfunction!(object::py::parse_stmt, m)?)?;
+    m.add_function(wrap_pyfunction!(object::py::tokenize_file, m)?)?;
+    m.add_function(wrap_pyfunction!(object::py::tokenize_source, m)?)?;
     Ok(())
 }
@@ -1,3 +1,26 @@
-fn main() {
-    println!("Hello, World!");
+use std::{path::PathBuf, process::ExitCode};
+
+use parse_py::{object::Object, project::Project};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(path) = args.first() else {
+        eprintln!("usage: parse-py <project-dir | ->");
+        return ExitCode::FAILURE;
+    };
+    let module = if path == "-" {
+        Project::from_reader("stdin", std::io::stdin().lock())
+    } else {
+        Project::create(PathBuf::from(path)).map(|project| project.root_ob)
+    };
+    match module {
+        Ok(module) => {
+            Object::Module(module).dump_tree_sorted();
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,12 +1,19 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     fmt::{self, Display},
     hash::{Hash, Hasher},
     os::unix::prelude::OsStrExt,
     path::{Component, Path, PathBuf},
+    sync::Arc,
 };
 
-use rustpython_parser::ast::{Arg, Arguments, ExcepthandlerKind, Location, Stmt, StmtKind};
+use rustpython_parser::{
+    ast::{
+        AliasData, Arg, Arguments, Boolop, Cmpop, Comprehension, Constant, ExcepthandlerKind, Expr,
+        ExprContext, ExprKind, Location, Operator, Stmt, StmtKind, Unaryop,
+    },
+    token::Tok,
+};
 
 pub mod py;
 
@@ -49,6 +56,72 @@ impl SourceSpan {
     pub fn end(&self) -> usize {
         self.end
     }
+
+    /// Whether this span fully contains the line range `start..=end`.
+    pub fn contains(&self, start: usize, end: usize) -> bool {
+        self.start <= start && end <= self.end
+    }
+
+    /// Maps the start of this span back through `map` to the original
+    /// `(unit, line)` it came from, for spans whose line numbers refer
+    /// to a synthetic combined buffer. Returns `None` if the map does
+    /// not cover the span.
+    pub fn original_location(&self, map: &LineMap) -> Option<(String, usize)> {
+        map.lookup(self.start)
+            .map(|(unit, line)| (unit.to_string(), line))
+    }
+
+    /// Returns a copy of this span with its path made relative to `root`.
+    /// The path is left unchanged if it is not under `root`.
+    pub fn relative_to(&self, root: &Path) -> SourceSpan {
+        let path = self
+            .path
+            .strip_prefix(root)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| self.path.clone());
+        SourceSpan {
+            path,
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+/// Maps line numbers in a synthetic combined buffer — e.g. concatenated
+/// notebook cells, or several sources glued together before parsing —
+/// back to the `(unit, line)` they came from. The spans produced by a
+/// parse of such a buffer refer to the buffer; looking them up here
+/// recovers positions meaningful to the user.
+#[derive(Debug, Clone, Default)]
+pub struct LineMap {
+    // (first synthetic line, unit name, first original line),
+    // in ascending order of synthetic line.
+    segments: Vec<(usize, String, usize)>,
+}
+
+impl LineMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers that synthetic lines from `start_line` up to the next
+    /// segment come from `unit`, whose own numbering begins at
+    /// `unit_start_line`. Segments must be added in ascending order of
+    /// `start_line`.
+    pub fn add_segment(&mut self, start_line: usize, unit: String, unit_start_line: usize) {
+        self.segments.push((start_line, unit, unit_start_line));
+    }
+
+    /// The original `(unit, line)` for a synthetic `line`, or `None`
+    /// if the line precedes every segment.
+    pub fn lookup(&self, line: usize) -> Option<(&str, usize)> {
+        let idx = self
+            .segments
+            .partition_point(|(start, _, _)| *start <= line)
+            .checked_sub(1)?;
+        let (start, unit, unit_start) = &self.segments[idx];
+        Some((unit, unit_start + (line - start)))
+    }
 }
 
 impl From<SourceSpan> for Position {
@@ -80,9 +153,24 @@ impl ObjectPath {
         self.components.last().unwrap()
     }
 
+    /// The dotted components of this path, in order.
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
     pub fn replace_name(&mut self, new_name: String) {
         *self.components.last_mut().unwrap() = new_name;
     }
+
+    /// Drops the leading component when it equals `name`, so a source
+    /// root like `src/` does not show up in dotted paths. A one-part
+    /// path is left alone: the source-root module itself keeps its
+    /// name.
+    pub fn strip_root(&mut self, name: &str) {
+        if self.components.len() > 1 && self.components[0] == name {
+            self.components.remove(0);
+        }
+    }
 }
 
 impl Display for ObjectPath {
@@ -104,6 +192,11 @@ pub struct ObjectData {
     children: HashMap<String, Object>,
     alt_cnts: HashMap<String, i32>,
     obj_path: ObjectPath,
+    module_path: ObjectPath,
+    decorator_sources: Vec<String>,
+    type_checking_only: bool,
+    qualname: String,
+    enclosing_class: Option<ObjectPath>,
 }
 
 impl ObjectData {
@@ -113,9 +206,57 @@ impl ObjectData {
             children: HashMap::new(),
             alt_cnts: HashMap::new(),
             obj_path,
+            module_path: ObjectPath::default(),
+            decorator_sources: Vec::new(),
+            type_checking_only: false,
+            qualname: String::new(),
+            enclosing_class: None,
         }
     }
 
+    /// The CPython-style qualified name of this object, as its
+    /// `__qualname__` would read at runtime: nesting joined with `.`,
+    /// with `<locals>` inserted below each enclosing function. Empty
+    /// for a module, which has no `__qualname__`.
+    pub fn qualname(&self) -> &str {
+        &self.qualname
+    }
+
+    /// The canonical path of the nearest ancestor of this object that
+    /// is a class, or `None` outside any class. Set once the whole
+    /// tree is built; `None` until then.
+    pub fn enclosing_class(&self) -> Option<&ObjectPath> {
+        self.enclosing_class.as_ref()
+    }
+
+    /// The source span this object covers.
+    pub fn span(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    /// The canonical dotted path of this object in the project tree.
+    pub fn obj_path(&self) -> &ObjectPath {
+        &self.obj_path
+    }
+
+    /// Whether this object was defined under an `if TYPE_CHECKING:`
+    /// block, and so only exists for the benefit of type checkers.
+    pub fn type_checking_only(&self) -> bool {
+        self.type_checking_only
+    }
+
+    /// The canonical path of the module this object was defined in.
+    /// For a module, this is its own path.
+    pub fn module_path(&self) -> &ObjectPath {
+        &self.module_path
+    }
+
+    /// The verbatim source text of each decorator on this object,
+    /// captured only when [`ModuleCreator::capture_decorator_source`] was used.
+    pub fn decorator_sources(&self) -> &[String] {
+        &self.decorator_sources
+    }
+
     pub fn name(&self) -> &str {
         self.obj_path.name()
     }
@@ -161,11 +302,53 @@ impl Hash for ObjectData {
     }
 }
 
+/// How completely a module was parsed. `Ok` means its file parsed
+/// cleanly. `Partial` means the module itself parsed but some of its
+/// files or subpackages were skipped during a lenient scan, so its
+/// object tree may be incomplete. `Skipped` means the source failed to
+/// parse and the module is an empty placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseStatus {
+    Ok,
+    Partial,
+    Skipped,
+}
+
+impl ParseStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ParseStatus::Ok => "ok",
+            ParseStatus::Partial => "partial",
+            ParseStatus::Skipped => "skipped",
+        }
+    }
+}
+
 /// Represents a Python module, which is basically all the stuff
 /// in a file.
 #[derive(Debug, Clone)]
 pub struct Module {
     data: ObjectData,
+    line_map: Option<LineMap>,
+    parse_status: ParseStatus,
+    imports: Vec<ImportRecord>,
+    dunder_all: Option<Vec<String>>,
+}
+
+/// One import in a module's load-time code: a plain `import a.b`
+/// yields one record per alias with empty `names`; a
+/// `from .pkg import x, y` yields a single record carrying the names
+/// and the relative level.
+#[derive(Debug, Clone)]
+pub struct ImportRecord {
+    /// The dotted module named: an alias of a plain `import`, or the
+    /// `from` module (empty for a bare `from . import x`).
+    pub module: String,
+    /// The names a `from` import binds; empty for a plain `import`.
+    pub names: Vec<String>,
+    /// The number of leading dots of a relative `from` import; zero
+    /// otherwise.
+    pub level: usize,
 }
 
 impl Display for Module {
@@ -179,16 +362,535 @@ impl Module {
         self.data.name()
     }
 
+    /// The canonical dotted path of this module in the project tree.
+    pub fn path(&self) -> &ObjectPath {
+        self.data.obj_path()
+    }
+
+    /// Whether this module came from a package's `__init__.py`.
+    pub fn is_package(&self) -> bool {
+        self.data
+            .span
+            .path
+            .file_name()
+            .is_some_and(|name| name == "__init__.py")
+    }
+
+    /// The imports this module performs at load time, in source order.
+    /// Imports inside control-flow blocks and class bodies count;
+    /// function-local imports do not, since those are deferred by
+    /// design (often precisely to break an import cycle).
+    pub fn imports(&self) -> &[ImportRecord] {
+        &self.imports
+    }
+
+    /// Resolves an import written in this module to the project-local
+    /// module or object defining it. `name` is the dotted text of the
+    /// import target — the `from` module plus the imported name, or
+    /// the module of a plain `import` — and `level` its leading dots,
+    /// as on [`ImportRecord`]. Only this module's own subtree is
+    /// searchable, so the project root sees every import, while a
+    /// relative level that climbs above this module — or any import
+    /// from outside the project — resolves to `None`.
+    pub fn resolve_import(&self, name: &str, level: usize) -> Option<ObjectPath> {
+        let mut modules: Vec<&Module> = vec![self];
+        modules.extend(self.all_submodules());
+        let index: HashMap<String, &Module> = modules
+            .into_iter()
+            .map(|m| (m.path().to_string(), m))
+            .collect();
+        // A dotted path names either a module or a top-level object of
+        // one: `pkg.mod` is tried as the module `pkg.mod` before the
+        // definition `mod` inside `pkg`.
+        let resolve = |dotted: &str| -> Option<ObjectPath> {
+            if let Some(module) = index.get(dotted) {
+                return Some(module.path().clone());
+            }
+            let (prefix, last) = dotted.rsplit_once('.')?;
+            let module = index.get(prefix)?;
+            module
+                .find_all_by_path(last)
+                .first()
+                .map(|ob| ob.data().obj_path().clone())
+        };
+        if level == 0 {
+            // Absolute imports may or may not spell out this module's
+            // own dotted path.
+            return resolve(name).or_else(|| resolve(&format!("{}.{}", self.path(), name)));
+        }
+        let comps = self.path().components();
+        let in_package = comps.len() + usize::from(self.is_package());
+        let keep = in_package.checked_sub(level)?;
+        let mut base = comps[..keep].join(".");
+        if !name.is_empty() {
+            base = format!("{}.{}", base, name);
+        }
+        resolve(&base)
+    }
+
+    /// The names a literal module-level `__all__` declares, if any:
+    /// a `list` or `tuple` of string constants assigned to the plain
+    /// name `__all__` at top level. Computed forms (`+=`,
+    /// concatenation, comprehensions) are not evaluated.
+    pub fn dunder_all(&self) -> Option<&[String]> {
+        self.dunder_all.as_deref()
+    }
+
+    /// The direct children making up this module's public API, sorted
+    /// by name: the names `__all__` lists when the module declares one
+    /// (see [`Module::dunder_all`]), otherwise every child whose name
+    /// has no leading underscore. Alternates count under their base
+    /// name.
+    pub fn public_api(&self) -> Vec<&Object> {
+        let mut public: Vec<&Object> = self
+            .children()
+            .filter(|child| {
+                let name = alt_base_name(child.data().name());
+                match &self.dunder_all {
+                    Some(all) => all.iter().any(|n| n == name),
+                    None => !name.starts_with('_'),
+                }
+            })
+            .collect();
+        public.sort_by_key(|ob| ob.data().name().to_string());
+        public
+    }
+
+    /// The number of public direct children; the length of
+    /// [`Module::public_api`].
+    pub fn public_api_count(&self) -> usize {
+        self.public_api().len()
+    }
+
+    /// The lint-suppression directives in this module's file, as
+    /// `(line, text)` pairs: the comments whose text contains one of
+    /// `directives`, e.g. `noqa`, `type: ignore` or `pragma: no
+    /// cover`. Goes through the lexer, so a `#` inside a string never
+    /// counts. Reads the source file.
+    pub fn suppression_comments(
+        &self,
+        directives: &[&str],
+    ) -> std::io::Result<Vec<(usize, String)>> {
+        let comments = comments_in_file(self.data.span.path())?;
+        Ok(comments
+            .into_iter()
+            .filter(|(_, text)| directives.iter().any(|d| text.contains(d)))
+            .collect())
+    }
+
+    /// Attaches a [`LineMap`] for modules parsed from a synthetic
+    /// combined buffer, so that span line numbers can be mapped back to
+    /// the original units via [`SourceSpan::original_location`].
+    pub fn set_line_map(&mut self, line_map: LineMap) {
+        self.line_map = Some(line_map);
+    }
+
+    /// The line map attached with [`Module::set_line_map`], if any.
+    pub fn line_map(&self) -> Option<&LineMap> {
+        self.line_map.as_ref()
+    }
+
+    /// How completely this module was parsed; see [`ParseStatus`].
+    pub fn parse_status(&self) -> ParseStatus {
+        self.parse_status
+    }
+
+    pub fn set_parse_status(&mut self, status: ParseStatus) {
+        self.parse_status = status;
+    }
+
     pub fn append_child(&mut self, child: Object) {
         self.data
             .append_child(child.data().name().to_string(), child);
     }
+
+    /// Whether any direct child of this module is itself a module,
+    /// looking through alt-objects. A package without submodules is
+    /// "trivial" for the purposes of
+    /// [`crate::project::ProjectOptions::collapse_trivial_packages`].
+    pub fn has_submodules(&self) -> bool {
+        self.data
+            .children
+            .values()
+            .any(|child| matches!(child.sub_object().unwrap_or(child), Object::Module(_)))
+    }
+
+    /// Consumes this module and yields its direct children, for merging
+    /// them into an enclosing namespace. Their spans and paths are left
+    /// untouched and keep pointing at this module's file.
+    pub fn into_children(self) -> Vec<Object> {
+        self.data.children.into_values().collect()
+    }
+
+    /// Returns the names this module defines at top-level, sorted.
+    /// Alternate definitions (`foo#1`) are folded into their base name.
+    /// This is the set a `from x import y` in another module resolves against.
+    pub fn top_level_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .data
+            .children
+            .keys()
+            .map(|name| alt_base_name(name).to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Rewrites the spans of this module and all its descendants
+    /// to be relative to `root`.
+    pub fn make_spans_relative(&mut self, root: &Path) {
+        self.data.span = self.data.span.relative_to(root);
+        for child in self.data.children.values_mut() {
+            child.make_spans_relative(root);
+        }
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &Object> {
+        self.data.children.values()
+    }
+
+    /// The children of this module defined in the file at `path`. In a
+    /// namespace package (or a merged tree) one logical module can
+    /// aggregate definitions from several physical files; this narrows
+    /// them to a single one.
+    pub fn children_in_file(&self, path: &Path) -> Vec<&Object> {
+        self.children()
+            .filter(|child| child.data().span().path == path)
+            .collect()
+    }
+
+    /// Finds every definition at the dotted `path` below this module:
+    /// the primary object plus any alternates (`name#N`) sharing the
+    /// base name, at every level of the path. `path` is relative to
+    /// this module, so it does not include the module's own name.
+    pub fn find_all_by_path(&self, path: &str) -> Vec<&Object> {
+        let mut comps = path.split('.');
+        let Some(first) = comps.next() else {
+            return Vec::new();
+        };
+        let mut current: Vec<&Object> = self
+            .data
+            .children
+            .values()
+            .filter(|c| alt_base_name(c.data().name()) == first)
+            .collect();
+        for comp in comps {
+            let mut next = Vec::new();
+            for ob in current {
+                ob.children_with_base_name(comp, &mut next);
+            }
+            current = next;
+        }
+        current.sort_by_key(|ob| ob.data().name().to_string());
+        current
+    }
+
+    /// A pruned copy of this module containing only the objects at
+    /// `paths`, everything below them, and their ancestors — kept
+    /// even when not in the set, so the tree stays navigable. An
+    /// empty `paths` yields a childless copy of the module itself.
+    pub fn subtree(&self, paths: &[ObjectPath]) -> Module {
+        fn is_prefix(short: &[String], long: &[String]) -> bool {
+            short.len() <= long.len() && short.iter().zip(long).all(|(a, b)| a == b)
+        }
+
+        fn prune(ob: &Object, paths: &[ObjectPath]) -> Option<Object> {
+            let comps = ob.data().obj_path().components();
+            // An object on or below a requested path is kept whole.
+            if paths.iter().any(|p| is_prefix(p.components(), comps)) {
+                return Some(ob.clone());
+            }
+            // An ancestor of one is kept, with its children pruned in
+            // turn; anything else goes.
+            if !paths.iter().any(|p| is_prefix(comps, p.components())) {
+                return None;
+            }
+            let mut kept = ob.clone();
+            let data = kept.data_mut();
+            let children = std::mem::take(&mut data.children);
+            for (name, child) in children {
+                if let Some(child) = prune(&child, paths) {
+                    data.children.insert(name, child);
+                }
+            }
+            Some(kept)
+        }
+
+        let mut module = self.clone();
+        let children = std::mem::take(&mut module.data.children);
+        for (name, child) in children {
+            if let Some(child) = prune(&child, paths) {
+                module.data.children.insert(name, child);
+            }
+        }
+        module
+    }
+
+    /// A plain JSON form of this module and everything below it,
+    /// mirroring the shape of the `to_dict` methods on the Python
+    /// classes.
+    #[cfg(feature = "serde")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        data_json(&self.data, ObjectKind::Module.as_str())
+    }
+
+    /// Collects every function in this module and its descendants,
+    /// paired with its canonical path. Alternate definitions are
+    /// included under their `name#N` paths.
+    pub fn all_functions(&self) -> Vec<(&ObjectPath, &Function)> {
+        let mut funcs = Vec::new();
+        for child in self.data.children.values() {
+            child.collect_functions(&mut funcs);
+        }
+        funcs
+    }
+
+    /// Collects every class in this module and its descendants, paired
+    /// with its canonical path. Alternate definitions are included
+    /// under their `name#N` paths.
+    pub fn all_classes(&self) -> Vec<(&ObjectPath, &Class)> {
+        let mut classes = Vec::new();
+        for child in self.data.children.values() {
+            child.collect_classes(&mut classes);
+        }
+        classes
+    }
+
+    /// Collects every module below this one, recursively: the files of
+    /// this package, nested packages and theirs. This module itself is
+    /// not included. Modules wrapped by alt-objects count too.
+    pub fn all_submodules(&self) -> Vec<&Module> {
+        fn collect<'a>(ob: &'a Object, out: &mut Vec<&'a Module>) {
+            let ob = ob.sub_object().unwrap_or(ob);
+            if let Object::Module(module) = ob {
+                out.push(module);
+                for child in module.data.children.values() {
+                    collect(child, out);
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        for child in self.data.children.values() {
+            collect(child, &mut out);
+        }
+        out
+    }
+
+    /// The deepest object below this module whose span fully contains
+    /// the line range `start..=end`, for "show enclosing symbol"
+    /// features. `None` means no child contains the range, leaving the
+    /// module itself as the closest cover.
+    pub fn enclosing_object(&self, start: usize, end: usize) -> Option<&Object> {
+        fn descend(ob: &Object, start: usize, end: usize) -> &Object {
+            for child in ob.children() {
+                if child.data().span.contains(start, end) {
+                    return descend(child, start, end);
+                }
+            }
+            ob
+        }
+
+        self.data
+            .children
+            .values()
+            .find(|child| child.data().span.contains(start, end))
+            .map(|child| descend(child, start, end))
+    }
+
+    /// Resolves every function's decorators against the definitions in
+    /// this tree, filling [`Function::resolved_decorators`]. A decorator
+    /// is looked up first in the function's own module and then as a
+    /// dotted path from the project root; decorators defined outside
+    /// the tree stay unresolved. Called once per project by
+    /// [`crate::project::Project`].
+    pub fn resolve_decorators(&mut self) {
+        fn build_index(ob: &Object, index: &mut HashMap<String, ObjectPath>) {
+            index
+                .entry(ob.data().obj_path.to_string())
+                .or_insert_with(|| ob.data().obj_path.clone());
+            for child in ob.children() {
+                build_index(child, index);
+            }
+        }
+
+        fn resolve(ob: &mut Object, index: &HashMap<String, ObjectPath>) {
+            if let Object::AltObject(a) = ob {
+                resolve(&mut a.sub_ob, index);
+            }
+            if let Object::Function(f) = ob {
+                let resolved = f
+                    .decorator_names()
+                    .iter()
+                    .map(|name| {
+                        // A decorator with arguments resolves by its callee.
+                        let base = name.split('(').next().unwrap().to_string();
+                        let module_local = format!("{}.{}", f.data.module_path, base);
+                        let path = index
+                            .get(&module_local)
+                            .or_else(|| index.get(&base))
+                            .cloned();
+                        (base, path)
+                    })
+                    .collect();
+                f.resolved_decorators = resolved;
+            }
+            for child in ob.data_mut().children.values_mut() {
+                resolve(child, index);
+            }
+        }
+
+        let mut index = HashMap::new();
+        index.insert(self.data.obj_path.to_string(), self.data.obj_path.clone());
+        for child in self.data.children.values() {
+            build_index(child, &mut index);
+        }
+        for child in self.data.children.values_mut() {
+            resolve(child, &index);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn data_json(data: &ObjectData, kind: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": data.name(),
+        "path": data.obj_path.to_string(),
+        "span": {
+            "filename": data.span.path().display().to_string(),
+            "start_line": data.span.start,
+            "end_line": data.span.end,
+        },
+        "kind": kind,
+        "children": data
+            .children
+            .iter()
+            .map(|(name, child)| (name.clone(), child.to_json_value()))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+/// Strips the `#N` suffix given to alternate definitions, yielding
+/// the name the object has in actual Python code.
+pub fn alt_base_name(name: &str) -> &str {
+    match name.split_once('#') {
+        Some((base, _)) => base,
+        None => name,
+    }
+}
+
+/// Whether the rendered decorator `dec` refers to `name`: its dotted
+/// callee (arguments stripped) either equals `name` or ends in
+/// `.name`, so `@functools.wraps(f)` matches both `"functools.wraps"`
+/// and `"wraps"`.
+fn decorator_matches(dec: &str, name: &str) -> bool {
+    let base = dec.split('(').next().unwrap();
+    base == name
+        || base
+            .strip_suffix(name)
+            .is_some_and(|pre| pre.ends_with('.'))
 }
 
 /// Represents a Python class.
 #[derive(Debug, Clone)]
 pub struct Class {
     data: ObjectData,
+    decorators: Vec<Expr>,
+    bases: Vec<Expr>,
+}
+
+impl Class {
+    /// The rendered base-class references of this class, in source
+    /// order: `["models.Model"]` for `class X(models.Model):`. Keyword
+    /// arguments like `metaclass=` are not part of the list.
+    pub fn base_names(&self) -> Vec<String> {
+        self.bases
+            .iter()
+            .map(|b| try_render_expr(&b.node))
+            .collect()
+    }
+
+    /// The rendered source of each decorator on this class, in order.
+    pub fn decorator_names(&self) -> Vec<String> {
+        self.decorators
+            .iter()
+            .map(|d| try_render_expr(&d.node))
+            .collect()
+    }
+
+    /// Whether one of this class's decorators refers to `name`. See
+    /// [`decorator_matches`] for what counts as a match.
+    pub fn has_decorator(&self, name: &str) -> bool {
+        self.decorator_names()
+            .iter()
+            .any(|dec| decorator_matches(dec, name))
+    }
+
+    /// The number of decorators on this class.
+    pub fn decorator_count(&self) -> usize {
+        self.decorators.len()
+    }
+
+    /// Whether this class is a dataclass-style record: decorated with
+    /// `@dataclass` (possibly with arguments) or one of the attrs
+    /// equivalents.
+    pub fn is_dataclass(&self) -> bool {
+        const MARKERS: &[&str] = &[
+            "dataclass",
+            "dataclasses.dataclass",
+            "attr.s",
+            "attr.attrs",
+            "attrs.define",
+            "attrs.frozen",
+        ];
+        self.decorator_names().iter().any(|name| {
+            let base = name.split('(').next().unwrap();
+            MARKERS.contains(&base)
+        })
+    }
+
+    /// The annotated class-level attributes of this class, in source
+    /// order. For a dataclass these are its fields.
+    pub fn fields(&self) -> Vec<&Variable> {
+        let mut fields: Vec<&Variable> = self
+            .data
+            .children
+            .values()
+            .filter_map(|child| match child {
+                Object::Variable(var) => Some(var),
+                _ => None,
+            })
+            .collect();
+        fields.sort_by_key(|var| var.data.span.start);
+        fields
+    }
+
+    /// The instance attributes this class's methods assign on `self`,
+    /// sorted and deduped: the union of the `self.x = ...` targets
+    /// (primarily from `__init__`) across every method, looking
+    /// through alternate definitions. Complements the annotation-based
+    /// [`Class::fields`] for documentation and completion.
+    pub fn instance_attributes(&self) -> Vec<String> {
+        let mut attrs: Vec<String> = self
+            .data
+            .children
+            .values()
+            .filter_map(|child| match child.sub_object().unwrap_or(child) {
+                Object::Function(func) => Some(func),
+                _ => None,
+            })
+            .flat_map(|func| {
+                func.self_attributes()
+                    .into_iter()
+                    .filter(|(_, _, write)| *write)
+                    .map(|(_, attr, _)| attr)
+            })
+            .collect();
+        attrs.sort();
+        attrs.dedup();
+        attrs
+    }
 }
 
 impl Display for Class {
@@ -197,8 +899,47 @@ impl Display for Class {
     }
 }
 
+/// Represents an annotated attribute declared at class scope, such as
+/// `x: int = 0` in a class body. These are the declarations dataclass
+/// and attrs fields are made of.
+#[derive(Debug, Clone)]
+pub struct Variable {
+    data: ObjectData,
+    annotation: String,
+    value: Option<String>,
+    simple: bool,
+}
+
+impl Variable {
+    /// The rendered annotation, e.g. `int` or `List[str]`.
+    pub fn annotation(&self) -> &str {
+        &self.annotation
+    }
+
+    /// The rendered assigned value, absent for a bare `x: int`.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Whether the target was a plain name (`x: int`), as opposed to a
+    /// parenthesized one (`(x): int`); from `AnnAssign`'s `simple` flag.
+    pub fn is_simple(&self) -> bool {
+        self.simple
+    }
+}
+
+impl Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var {}: {}", self.data.name(), self.annotation)
+    }
+}
+
 /// The kind of a formal parameter of a function.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormalParamKind {
     PosOnly,
     KwOnly,
@@ -206,91 +947,770 @@ pub enum FormalParamKind {
 }
 
 /// Denotes a formal parameter of a function.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FormalParam {
     pub name: String,
     pub has_default: bool,
     pub kind: FormalParamKind,
 }
 
+/// An f-string occurrence in a function body: its line number, its
+/// literal parts, and the rendered interpolated expressions.
+pub type FString = (usize, Vec<String>, Vec<String>);
+
+/// One `with` item entered in a function body: its line number, the
+/// rendered context expression, the rendered `as` target when
+/// present, and whether the `with` was `async`.
+pub type ContextManager = (usize, String, Option<String>, bool);
+
 /// Represents a function in Python, either top-level,
 /// or part of a class.
 #[derive(Debug, Clone)]
 pub struct Function {
     data: ObjectData,
     args: Arguments,
+    /// Every statement in the function keyed by line, with compound
+    /// statements listed both as themselves and as their contents, so
+    /// block nesting is flattened away. See [`Function::body`] for the
+    /// structural form.
     stmts: HashMap<usize, StmtKind>,
+    body: Vec<Stmt>,
+    decorators: Vec<Expr>,
+    /// Filled by [`Module::resolve_decorators`] after the whole tree is
+    /// built; empty until then.
+    resolved_decorators: Vec<(String, Option<ObjectPath>)>,
+    returns: Option<Box<Expr>>,
+    /// Whether statement flattening was skipped because the body
+    /// exceeded [`ModuleCreator::with_max_body_lines`]; `stmts` is empty
+    /// when set.
+    body_truncated: bool,
+    /// Whether the directly enclosing class inherits from `TestCase`,
+    /// noted at construction for [`Function::is_test`].
+    in_testcase: bool,
 }
 
 impl Function {
-    pub fn has_kwargs_dict(&self) -> bool {
-        self.args.kwarg.is_some()
+    /// Whether this function looks like a test by convention: its name
+    /// starts with `prefix`, it carries a decorator starting with one
+    /// of `markers` (`"pytest.mark"` matches
+    /// `@pytest.mark.parametrize(...)`), or it is a method named
+    /// `test...` on a `TestCase` subclass. Purely heuristic — nothing
+    /// is imported or resolved.
+    pub fn is_test(&self, prefix: &str, markers: &[&str]) -> bool {
+        if self.data.name().starts_with(prefix) {
+            return true;
+        }
+        if self.in_testcase && self.data.name().starts_with("test") {
+            return true;
+        }
+        self.decorator_names().iter().any(|dec| {
+            markers.iter().any(|marker| {
+                dec == marker
+                    || dec
+                        .strip_prefix(marker)
+                        .is_some_and(|rest| rest.starts_with(['.', '(']))
+            })
+        })
     }
 
-    pub fn kwargs_name(&self) -> String {
-        assert!(self.has_kwargs_dict());
-        self.args.kwarg.as_ref().unwrap().node.arg.to_string()
+    /// Whether this function's statements were left out because its
+    /// body exceeded the configured line cap. The signature and any
+    /// nested definitions are still present.
+    pub fn body_truncated(&self) -> bool {
+        self.body_truncated
     }
 
-    pub fn formal_params(&self) -> Vec<FormalParam> {
-        fn arg_names(args: &[Arg]) -> Vec<String> {
-            args.iter().map(|arg| arg.node.arg.clone()).collect()
+    /// Whether this function's body has no executable statements: only
+    /// `pass`, `...` and an optional leading docstring. Flags
+    /// unimplemented functions and protocol methods. False when the
+    /// body was truncated, since nothing is known about it then.
+    pub fn is_stub(&self) -> bool {
+        if self.body_truncated {
+            return false;
         }
+        self.body.iter().enumerate().all(|(i, stmt)| {
+            let StmtKind::Expr { value } = &stmt.node else {
+                return matches!(stmt.node, StmtKind::Pass);
+            };
+            match &value.node {
+                ExprKind::Constant { value, .. } => {
+                    matches!(value, Constant::Ellipsis)
+                        || (i == 0 && matches!(value, Constant::Str(_)))
+                }
+                _ => false,
+            }
+        })
+    }
 
-        let posonly = arg_names(&self.args.posonlyargs);
-        let normal = arg_names(&self.args.args);
-        let kwonly = arg_names(&self.args.kwonlyargs);
-
-        let def_cnt = self.args.defaults.len();
-        let norm_def_cnt = normal.len().min(def_cnt);
-        let posonly_def_cnt = posonly.len().min(def_cnt - norm_def_cnt);
-        let kwonly_def_cnt = self.args.kw_defaults.len();
+    /// The rendered source of each decorator on this function, in order.
+    pub fn decorator_names(&self) -> Vec<String> {
+        self.decorators
+            .iter()
+            .map(|d| try_render_expr(&d.node))
+            .collect()
+    }
 
-        let mut params = Vec::new();
+    /// Whether one of this function's decorators refers to `name`. See
+    /// [`decorator_matches`] for what counts as a match.
+    pub fn has_decorator(&self, name: &str) -> bool {
+        self.decorator_names()
+            .iter()
+            .any(|dec| decorator_matches(dec, name))
+    }
 
-        for (i, arg) in posonly.iter().enumerate() {
-            let has_default = i >= (posonly.len() - posonly_def_cnt);
-            params.push(FormalParam {
-                name: arg.to_string(),
-                has_default,
-                kind: FormalParamKind::PosOnly,
-            });
-        }
-        for (i, arg) in normal.iter().enumerate() {
-            let has_default = i >= (normal.len() - norm_def_cnt);
-            params.push(FormalParam {
-                name: arg.to_string(),
-                has_default,
-                kind: FormalParamKind::Normal,
-            });
-        }
-        for (i, arg) in kwonly.iter().enumerate() {
-            let has_default = i > (kwonly.len() - kwonly_def_cnt);
-            params.push(FormalParam {
-                name: arg.to_string(),
-                has_default,
-                kind: FormalParamKind::KwOnly,
-            })
-        }
+    /// The number of decorators on this function.
+    pub fn decorator_count(&self) -> usize {
+        self.decorators.len()
+    }
 
-        params
+    /// The decorators on this function paired with the project object
+    /// each one resolves to, where resolution succeeded. Decorators
+    /// defined outside the project resolve to `None`. Empty unless the
+    /// function came from a [`crate::project::Project`], since
+    /// resolution needs the whole tree.
+    pub fn resolved_decorators(&self) -> &[(String, Option<ObjectPath>)] {
+        &self.resolved_decorators
     }
 
-    pub fn format_args(&self) -> String {
-        fn make_arg_list(args: &[Arg]) -> String {
-            let mut list = String::new();
-            for (i, arg) in args.iter().enumerate() {
-                if i != 0 {
-                    list.push_str(", ");
+    /// Counts this function's source lines of code: the non-blank,
+    /// non-comment lines within its span, excluding its docstring.
+    /// Every physical line of a multi-line (continuation) statement
+    /// counts separately. Reads the file the span points into.
+    pub fn sloc(&self) -> std::io::Result<usize> {
+        let code = std::fs::read_to_string(self.data.span.path())?;
+        let lines: Vec<&str> = code.lines().collect();
+        let (start, end) = (self.data.span.start, self.data.span.end);
+
+        // The docstring runs from the first statement (if it is a string
+        // literal) up to the line before the next statement. Over-shooting
+        // is harmless, since only blank or comment lines can lie between.
+        let mut keys: Vec<usize> = self.stmts.keys().copied().collect();
+        keys.sort_unstable();
+        let mut doc_range = None;
+        if let Some(&first) = keys.first() {
+            if let Some(StmtKind::Expr { value }) = self.stmts.get(&first) {
+                if matches!(
+                    &value.node,
+                    ExprKind::Constant {
+                        value: Constant::Str(_),
+                        ..
+                    }
+                ) {
+                    let doc_end = keys.get(1).map(|&n| n - 1).unwrap_or(end);
+                    doc_range = Some((first, doc_end));
                 }
-                list.push_str(&arg.node.arg);
             }
-            list
         }
 
-        let args = make_arg_list(&self.args.args);
-        let posonly = make_arg_list(&self.args.posonlyargs);
-        let kwonly = make_arg_list(&self.args.kwonlyargs);
+        let mut cnt = 0;
+        for row in start..=end.min(lines.len()) {
+            let line = lines[row - 1].trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((ds, de)) = doc_range {
+                if row >= ds && row <= de {
+                    continue;
+                }
+            }
+            cnt += 1;
+        }
+        Ok(cnt)
+    }
+
+    /// The verbatim source text of this function: the lines its span
+    /// covers, joined with newlines. Reads the file the span points
+    /// into.
+    pub fn source(&self) -> std::io::Result<String> {
+        let code = std::fs::read_to_string(self.data.span.path())?;
+        let lines: Vec<&str> = code.lines().collect();
+        let (start, end) = (self.data.span.start, self.data.span.end);
+        Ok(lines[start - 1..end.min(lines.len())].join("\n"))
+    }
+
+    /// A unified diff of this function's source against `other`'s,
+    /// headed by the two dotted paths. Reads both source files. Ready
+    /// to present as "what changed in this function".
+    pub fn body_diff(&self, other: &Function) -> std::io::Result<String> {
+        let old = self.source()?;
+        let new = other.source()?;
+        let diff = similar::TextDiff::from_lines(&old, &new);
+        Ok(diff
+            .unified_diff()
+            .header(
+                &self.data.obj_path.to_string(),
+                &other.data.obj_path.to_string(),
+            )
+            .to_string())
+    }
+
+    /// Whether this function calls itself by name anywhere in its body.
+    /// Only direct self-calls are detected; mutual recursion is not.
+    pub fn is_recursive(&self) -> bool {
+        let name = alt_base_name(self.data.name());
+        let mut found = false;
+        for stmt in self.stmts.values() {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                if let ExprKind::Call { func, .. } = &expr.node {
+                    if let ExprKind::Name { id, .. } = &func.node {
+                        if id == name {
+                            found = true;
+                        }
+                    }
+                }
+            });
+        }
+        found
+    }
+
+    /// Whether `other` is structurally the same function as this one:
+    /// same formal parameters and the same statements in the same order,
+    /// compared by their rendered source rather than by the AST, so
+    /// positions (and therefore files) don't matter.
+    pub fn structurally_equal(&self, other: &Function) -> bool {
+        if self.formal_params() != other.formal_params() {
+            return false;
+        }
+        self.rendered_stmts() == other.rendered_stmts()
+    }
+
+    /// Whether `other` is structurally the same function as this one
+    /// once docstrings are set aside: same formal parameters and the
+    /// same statements, with each side's leading docstring excluded.
+    /// Comments never reach the statement map, so they never count.
+    /// Refines [`Function::structurally_equal`] for clone detection
+    /// that tolerates documentation-only differences.
+    pub fn equal_ignoring_docs(&self, other: &Function) -> bool {
+        if self.formal_params() != other.formal_params() {
+            return false;
+        }
+        self.rendered_stmts_no_docs() == other.rendered_stmts_no_docs()
+    }
+
+    /// This function's statements rendered in line order, with the
+    /// leading docstring left out.
+    fn rendered_stmts_no_docs(&self) -> Vec<String> {
+        let mut rendered = self.rendered_stmts();
+        let has_doc = self.body.first().is_some_and(|stmt| {
+            matches!(
+                &stmt.node,
+                StmtKind::Expr { value }
+                    if matches!(&value.node, ExprKind::Constant { value: Constant::Str(_), .. })
+            )
+        });
+        if has_doc && !rendered.is_empty() {
+            rendered.remove(0);
+        }
+        rendered
+    }
+
+    /// A deterministic hash of this function's signature and rendered
+    /// body. Structurally equal functions hash identically, making this
+    /// a cheap bucketing key for clone detection.
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.format_args().hash(&mut hasher);
+        self.rendered_stmts().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The number of `return` statements anywhere in this function's
+    /// body, from the flattened statement map.
+    pub fn return_count(&self) -> usize {
+        self.stmts
+            .values()
+            .filter(|stmt| matches!(stmt, StmtKind::Return { .. }))
+            .count()
+    }
+
+    /// The number of explicit exit points in this function's body:
+    /// `return` and `raise` statements combined. A quick risk signal
+    /// to pair with complexity on a maintainability dashboard.
+    pub fn exit_points(&self) -> usize {
+        self.stmts
+            .values()
+            .filter(|stmt| matches!(stmt, StmtKind::Return { .. } | StmtKind::Raise { .. }))
+            .count()
+    }
+
+    /// The context managers this function enters, one
+    /// `(line, context_expr, target, is_async)` entry per `with` item
+    /// sorted by line: the rendered expression (e.g. `open(path)`),
+    /// the rendered `as` target when present, and whether the `with`
+    /// was `async`. A multi-item `with` yields one entry per item,
+    /// all on the statement's line.
+    pub fn context_managers(&self) -> Vec<ContextManager> {
+        let mut managers = Vec::new();
+        for (&line, stmt) in &self.stmts {
+            let (items, is_async) = match stmt {
+                StmtKind::With { items, .. } => (items, false),
+                StmtKind::AsyncWith { items, .. } => (items, true),
+                _ => continue,
+            };
+            for item in items {
+                managers.push((
+                    line,
+                    render_expr(&item.context_expr.node),
+                    item.optional_vars.as_ref().map(|v| render_expr(&v.node)),
+                    is_async,
+                ));
+            }
+        }
+        managers.sort();
+        managers
+    }
+
+    /// The attribute accesses on `self` anywhere in this function's
+    /// body, as `(line, attr, is_write)` triples sorted by line:
+    /// `is_write` is set when the attribute is a store or delete
+    /// target, as in `self.x = ...`, and clear for plain reads.
+    /// Aggregated over a class's methods this reconstructs the
+    /// instance's attribute set.
+    pub fn self_attributes(&self) -> Vec<(usize, String, bool)> {
+        let mut accesses = Vec::new();
+        for (&line, stmt) in &self.stmts {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                let ExprKind::Attribute { value, attr, ctx } = &expr.node else {
+                    return;
+                };
+                if matches!(&value.node, ExprKind::Name { id, .. } if id == "self") {
+                    let write = matches!(ctx, ExprContext::Store | ExprContext::Del);
+                    accesses.push((line, attr.clone(), write));
+                }
+            });
+        }
+        accesses.sort();
+        accesses
+    }
+
+    /// The yield expressions anywhere in this function's body, as
+    /// `(line, text)` pairs sorted by line: `yield x` and
+    /// `yield from xs` rendered back to source, a bare `yield` as
+    /// just that. A non-empty result marks a generator (or, with
+    /// `async def`, an async generator).
+    pub fn yields(&self) -> Vec<(usize, String)> {
+        let mut yields = Vec::new();
+        for (&line, stmt) in &self.stmts {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                if matches!(
+                    expr.node,
+                    ExprKind::Yield { .. } | ExprKind::YieldFrom { .. }
+                ) {
+                    yields.push((line, render_expr(&expr.node)));
+                }
+            });
+        }
+        yields.sort();
+        yields
+    }
+
+    /// The calls this function makes to one of `names` (typically
+    /// `print`, `breakpoint`, `pdb.set_trace`), as `(line, name)` pairs
+    /// sorted by line. Made for flushing out print-debugging leftovers;
+    /// pass a custom set to catch project-specific loggers too.
+    pub fn debug_calls(&self, names: &[&str]) -> Vec<(usize, String)> {
+        let mut calls = Vec::new();
+        for (&line, stmt) in &self.stmts {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                if let ExprKind::Call { func, .. } = &expr.node {
+                    let callee = render_expr(&func.node);
+                    if names.contains(&callee.as_str()) {
+                        calls.push((line, callee));
+                    }
+                }
+            });
+        }
+        calls.sort();
+        calls
+    }
+
+    /// The shape of this function's signature in one cheap call:
+    /// `(posonly, normal, kwonly, has_vararg, has_kwarg)` counts of
+    /// each parameter group.
+    pub fn arity(&self) -> (usize, usize, usize, bool, bool) {
+        (
+            self.args.posonlyargs.len(),
+            self.args.args.len(),
+            self.args.kwonlyargs.len(),
+            self.args.vararg.is_some(),
+            self.args.kwarg.is_some(),
+        )
+    }
+
+    /// The parameters of this function in declaration order, as
+    /// `(name, kind, annotation, default)` tuples with `kind` one of
+    /// `"posonly"`, `"normal"`, `"vararg"`, `"kwonly"` or `"kwarg"`.
+    /// Annotations and default values are rendered back to source text.
+    pub fn parameters(&self) -> Vec<(String, &'static str, Option<String>, Option<String>)> {
+        type Param = (String, &'static str, Option<String>, Option<String>);
+
+        fn push_group(params: &mut Vec<Param>, group: &[Arg], kind: &'static str, defs: &[Expr]) {
+            let no_default = group.len().saturating_sub(defs.len());
+            for (i, arg) in group.iter().enumerate() {
+                let annotation = arg
+                    .node
+                    .annotation
+                    .as_ref()
+                    .map(|ann| try_render_expr(&ann.node));
+                let default = i
+                    .checked_sub(no_default)
+                    .and_then(|d| defs.get(d))
+                    .map(|def| try_render_expr(&def.node));
+                params.push((arg.node.arg.to_string(), kind, annotation, default));
+            }
+        }
+
+        // `defaults` covers the trailing positional parameters, filling
+        // the normal group before spilling into the positional-only one.
+        let args = &self.args;
+        let normal_defs = args.defaults.len().min(args.args.len());
+        let pos_defs = args.defaults.len() - normal_defs;
+
+        let mut params = Vec::new();
+        push_group(
+            &mut params,
+            &args.posonlyargs,
+            "posonly",
+            &args.defaults[..pos_defs],
+        );
+        push_group(
+            &mut params,
+            &args.args,
+            "normal",
+            &args.defaults[pos_defs..],
+        );
+        if let Some(vararg) = &args.vararg {
+            let annotation = vararg
+                .node
+                .annotation
+                .as_ref()
+                .map(|ann| try_render_expr(&ann.node));
+            params.push((vararg.node.arg.to_string(), "vararg", annotation, None));
+        }
+        push_group(&mut params, &args.kwonlyargs, "kwonly", &args.kw_defaults);
+        if let Some(kwarg) = &args.kwarg {
+            let annotation = kwarg
+                .node
+                .annotation
+                .as_ref()
+                .map(|ann| try_render_expr(&ann.node));
+            params.push((kwarg.node.arg.to_string(), "kwarg", annotation, None));
+        }
+        params
+    }
+
+    /// The assert statements in this function as `(line, test, message)`
+    /// triples sorted by line, with the test expression and optional
+    /// message rendered back to source text. A test function coming
+    /// back empty is an easy smell to flag.
+    pub fn asserts(&self) -> Vec<(usize, String, Option<String>)> {
+        let mut asserts: Vec<_> = self
+            .stmts
+            .iter()
+            .filter_map(|(&line, stmt)| match stmt {
+                StmtKind::Assert { test, msg } => Some((
+                    line,
+                    try_render_expr(&test.node),
+                    msg.as_ref().map(|m| try_render_expr(&m.node)),
+                )),
+                _ => None,
+            })
+            .collect();
+        asserts.sort_by_key(|(line, ..)| *line);
+        asserts
+    }
+
+    /// The names this function declares `global`, sorted and deduped.
+    /// A function with any of these mutates (or at least rebinds)
+    /// module-level state.
+    pub fn global_names(&self) -> Vec<String> {
+        self.declared_names(|kind| match kind {
+            StmtKind::Global { names } => Some(names),
+            _ => None,
+        })
+    }
+
+    /// The names this function declares `nonlocal`, sorted and deduped.
+    pub fn nonlocal_names(&self) -> Vec<String> {
+        self.declared_names(|kind| match kind {
+            StmtKind::Nonlocal { names } => Some(names),
+            _ => None,
+        })
+    }
+
+    fn declared_names(&self, pick: impl Fn(&StmtKind) -> Option<&Vec<String>>) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .stmts
+            .values()
+            .filter_map(pick)
+            .flatten()
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// A normalized key for this function's signature shape, ignoring
+    /// parameter names but keeping arity, kinds, annotations and
+    /// defaults, e.g. `(pos=2, normal=1<int>[1def], kwonly=0, *args,
+    /// **kwargs) -> int`. Functions with signature-compatible
+    /// definitions (overloads, overrides) share a key regardless of how
+    /// their parameters are named.
+    pub fn signature_key(&self) -> String {
+        fn group(kind: &str, args: &[Arg], defaults: usize) -> String {
+            let mut key = format!("{}={}", kind, args.len());
+            if args.iter().any(|a| a.node.annotation.is_some()) {
+                let anns: Vec<String> = args
+                    .iter()
+                    .map(|a| {
+                        a.node
+                            .annotation
+                            .as_ref()
+                            .map_or_else(|| "_".to_string(), |ann| render_expr(&ann.node))
+                    })
+                    .collect();
+                key.push_str(&format!("<{}>", anns.join(",")));
+            }
+            if defaults > 0 {
+                key.push_str(&format!("[{}def]", defaults));
+            }
+            key
+        }
+
+        // `defaults` covers the trailing positional parameters, filling
+        // the normal group before spilling into the positional-only one.
+        let normal_defs = self.args.defaults.len().min(self.args.args.len());
+        let pos_defs = self.args.defaults.len() - normal_defs;
+
+        let mut parts = vec![
+            group("pos", &self.args.posonlyargs, pos_defs),
+            group("normal", &self.args.args, normal_defs),
+            group("kwonly", &self.args.kwonlyargs, self.args.kw_defaults.len()),
+        ];
+        if let Some(vararg) = &self.args.vararg {
+            match &vararg.node.annotation {
+                Some(ann) => parts.push(format!("*args<{}>", render_expr(&ann.node))),
+                None => parts.push("*args".to_string()),
+            }
+        }
+        if let Some(kwarg) = &self.args.kwarg {
+            match &kwarg.node.annotation {
+                Some(ann) => parts.push(format!("**kwargs<{}>", render_expr(&ann.node))),
+                None => parts.push("**kwargs".to_string()),
+            }
+        }
+        let mut key = format!("({})", parts.join(", "));
+        if let Some(returns) = &self.returns {
+            key.push_str(&format!(" -> {}", render_expr(&returns.node)));
+        }
+        key
+    }
+
+    /// The function's direct body statements in source order, with
+    /// their real block structure intact. This is the statement list to
+    /// use for control-flow analysis; the flattened per-line map mixes
+    /// compound statements with their own contents.
+    pub fn body(&self) -> &[Stmt] {
+        &self.body
+    }
+
+    /// The number of statements in this function's flattened statement
+    /// map. Compound statements count once for themselves on top of
+    /// their bodies' statements.
+    pub fn statement_count(&self) -> usize {
+        self.stmts.len()
+    }
+
+    /// The last statement of this function's direct body, for checks
+    /// like "does it end in a `return`". `None` when the body is empty
+    /// or was truncated.
+    pub fn last_statement(&self) -> Option<&Stmt> {
+        self.body.last()
+    }
+
+    /// The flattened statements of this function ordered by their line
+    /// key, saving every consumer a sort of the `stmts` map. Line
+    /// order approximates source order; a compound statement sorts
+    /// before its contents.
+    pub fn statements_sorted(&self) -> Vec<(usize, &StmtKind)> {
+        let mut stmts: Vec<(usize, &StmtKind)> = self
+            .stmts
+            .iter()
+            .map(|(line, stmt)| (*line, stmt))
+            .collect();
+        stmts.sort_by_key(|(line, _)| *line);
+        stmts
+    }
+
+    /// This function's statements rendered in line order.
+    fn rendered_stmts(&self) -> Vec<String> {
+        let mut keys: Vec<usize> = self.stmts.keys().copied().collect();
+        keys.sort_unstable();
+        keys.into_iter()
+            .map(|k| render_stmt(&self.stmts[&k]))
+            .collect()
+    }
+
+    /// Counts the distinct callees this function invokes: the number of
+    /// unique rendered callee expressions across all calls in its body.
+    /// `self.x()` and `f()` both count; attribute chains collapse to
+    /// their full dotted name.
+    pub fn fan_out(&self) -> usize {
+        let mut callees = HashSet::new();
+        for stmt in self.stmts.values() {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                if let ExprKind::Call { func, .. } = &expr.node {
+                    callees.insert(render_expr(&func.node));
+                }
+            });
+        }
+        callees.len()
+    }
+
+    /// Every string literal in this function's body, paired with its
+    /// line number. Includes the literal parts of f-strings; byte
+    /// strings are not literal text and are excluded. Sorted by line.
+    pub fn string_literals(&self) -> Vec<(usize, String)> {
+        let mut literals = Vec::new();
+        for stmt in self.stmts.values() {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                if let ExprKind::Constant {
+                    value: Constant::Str(s),
+                    ..
+                } = &expr.node
+                {
+                    literals.push((expr.location.row(), s.clone()));
+                }
+            });
+        }
+        literals.sort();
+        literals
+    }
+
+    /// Every f-string in this function's body, as its line number, its
+    /// literal parts and the rendered source of its interpolated
+    /// expressions, in order of appearance. Sorted by line.
+    pub fn fstrings(&self) -> Vec<FString> {
+        let mut fstrings = Vec::new();
+        for stmt in self.stmts.values() {
+            visit_stmt_exprs(stmt, &mut |expr| {
+                let ExprKind::JoinedStr { values } = &expr.node else {
+                    return;
+                };
+                let mut literals = Vec::new();
+                let mut interpolations = Vec::new();
+                for value in values {
+                    match &value.node {
+                        ExprKind::Constant {
+                            value: Constant::Str(s),
+                            ..
+                        } => literals.push(s.clone()),
+                        ExprKind::FormattedValue { value, .. } => {
+                            interpolations.push(render_expr(&value.node))
+                        }
+                        _ => {}
+                    }
+                }
+                fstrings.push((expr.location.row(), literals, interpolations));
+            });
+        }
+        fstrings.sort();
+        fstrings
+    }
+
+    pub fn has_kwargs_dict(&self) -> bool {
+        self.args.kwarg.is_some()
+    }
+
+    pub fn kwargs_name(&self) -> String {
+        assert!(self.has_kwargs_dict());
+        self.args.kwarg.as_ref().unwrap().node.arg.to_string()
+    }
+
+    /// The name of the `*args`-style vararg, if the function has one.
+    pub fn vararg_name(&self) -> Option<String> {
+        self.args.vararg.as_ref().map(|arg| arg.node.arg.clone())
+    }
+
+    /// The name of the `**kwargs`-style dict, if the function has one.
+    pub fn kwarg_name(&self) -> Option<String> {
+        self.args.kwarg.as_ref().map(|arg| arg.node.arg.clone())
+    }
+
+    /// Maps each keyword-only parameter that has a default onto the
+    /// rendered source of that default.
+    pub fn kwonly_defaults(&self) -> HashMap<String, String> {
+        let kwonly = &self.args.kwonlyargs;
+        let defaults = &self.args.kw_defaults;
+        let offset = kwonly.len() - defaults.len();
+        kwonly
+            .iter()
+            .skip(offset)
+            .zip(defaults)
+            .map(|(arg, def)| (arg.node.arg.clone(), render_expr(&def.node)))
+            .collect()
+    }
+
+    pub fn formal_params(&self) -> Vec<FormalParam> {
+        fn arg_names(args: &[Arg]) -> Vec<String> {
+            args.iter().map(|arg| arg.node.arg.clone()).collect()
+        }
+
+        let posonly = arg_names(&self.args.posonlyargs);
+        let normal = arg_names(&self.args.args);
+        let kwonly = arg_names(&self.args.kwonlyargs);
+
+        let def_cnt = self.args.defaults.len();
+        let norm_def_cnt = normal.len().min(def_cnt);
+        let posonly_def_cnt = posonly.len().min(def_cnt - norm_def_cnt);
+        let kwonly_def_cnt = self.args.kw_defaults.len();
+
+        let mut params = Vec::new();
+
+        for (i, arg) in posonly.iter().enumerate() {
+            let has_default = i >= (posonly.len() - posonly_def_cnt);
+            params.push(FormalParam {
+                name: arg.to_string(),
+                has_default,
+                kind: FormalParamKind::PosOnly,
+            });
+        }
+        for (i, arg) in normal.iter().enumerate() {
+            let has_default = i >= (normal.len() - norm_def_cnt);
+            params.push(FormalParam {
+                name: arg.to_string(),
+                has_default,
+                kind: FormalParamKind::Normal,
+            });
+        }
+        for (i, arg) in kwonly.iter().enumerate() {
+            let has_default = i > (kwonly.len() - kwonly_def_cnt);
+            params.push(FormalParam {
+                name: arg.to_string(),
+                has_default,
+                kind: FormalParamKind::KwOnly,
+            })
+        }
+
+        params
+    }
+
+    pub fn format_args(&self) -> String {
+        fn make_arg_list(args: &[Arg]) -> String {
+            let mut list = String::new();
+            for (i, arg) in args.iter().enumerate() {
+                if i != 0 {
+                    list.push_str(", ");
+                }
+                list.push_str(&arg.node.arg);
+            }
+            list
+        }
+
+        let args = make_arg_list(&self.args.args);
+        let posonly = make_arg_list(&self.args.posonlyargs);
+        let kwonly = make_arg_list(&self.args.kwonlyargs);
 
         let mut out = String::new();
         if !posonly.is_empty() {
@@ -308,6 +1728,14 @@ impl Function {
                 out.push_str(", ");
                 out.push_str(&kwonly);
             }
+        } else if !kwonly.is_empty() {
+            // A bare `*` separator: keyword-only parameters without a
+            // vararg to collect the overflow.
+            if !out.is_empty() {
+                out.push_str(", ");
+            }
+            out.push_str("*, ");
+            out.push_str(&kwonly);
         }
         if let Some(kwarg) = &self.args.kwarg {
             if (!out.is_empty() && out.as_bytes().last().unwrap() != &b'/') || !out.is_empty() {
@@ -355,7 +1783,8 @@ impl AltObject {
     ) -> Self {
         let alt_name = format!("{}#{}", obj_path.name(), alt_cnt);
         obj_path.replace_name(alt_name);
-        let data = ObjectData::new(source_span, obj_path);
+        let mut data = ObjectData::new(source_span, obj_path);
+        data.module_path = sub_ob.data().module_path.clone();
         Self {
             data,
             sub_ob: Box::new(sub_ob),
@@ -363,12 +1792,47 @@ impl AltObject {
     }
 }
 
+/// The canonical kind of an [`Object`]. [`ObjectKind::as_str`] is the
+/// one spelling used everywhere a kind appears as text: `ob_type`, the
+/// Python `to_dict` output, outlines and the serde JSON form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Module,
+    Class,
+    Function,
+    Variable,
+    Alt,
+}
+
+impl ObjectKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ObjectKind::Module => "module",
+            ObjectKind::Class => "class",
+            ObjectKind::Function => "function",
+            ObjectKind::Variable => "variable",
+            ObjectKind::Alt => "alt",
+        }
+    }
+}
+
+impl Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// This is an entity in Python, such as module, class or function.
+// Functions carry their arguments and statements inline, which makes
+// the variant big; boxing it would ripple through every match on the
+// enum for little gain, since objects mostly live behind collections.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum Object {
     Module(Module),
     Class(Class),
     Function(Function),
+    Variable(Variable),
     AltObject(AltObject),
 }
 
@@ -378,15 +1842,40 @@ impl Object {
             Object::Module(m) => &m.data,
             Object::Class(c) => &c.data,
             Object::Function(f) => &f.data,
+            Object::Variable(v) => &v.data,
             Object::AltObject(a) => &a.data,
         }
     }
 
+    pub fn data_mut(&mut self) -> &mut ObjectData {
+        match self {
+            Object::Module(m) => &mut m.data,
+            Object::Class(c) => &mut c.data,
+            Object::Function(f) => &mut f.data,
+            Object::Variable(v) => &mut v.data,
+            Object::AltObject(a) => &mut a.data,
+        }
+    }
+
+    /// Rewrites the spans of this object and all its descendants
+    /// to be relative to `root`.
+    pub fn make_spans_relative(&mut self, root: &Path) {
+        if let Object::AltObject(a) = self {
+            a.sub_ob.make_spans_relative(root);
+        }
+        let data = self.data_mut();
+        data.span = data.span.relative_to(root);
+        for child in data.children.values_mut() {
+            child.make_spans_relative(root);
+        }
+    }
+
     pub fn into_data(self) -> ObjectData {
         match self {
             Object::Module(m) => m.data,
             Object::Class(c) => c.data,
             Object::Function(f) => f.data,
+            Object::Variable(v) => v.data,
             Object::AltObject(a) => a.data,
         }
     }
@@ -395,16 +1884,232 @@ impl Object {
         self.into_data().children.into_values()
     }
 
-    pub fn ob_type(&self) -> &'static str {
+    pub fn children(&self) -> impl Iterator<Item = &Object> {
+        self.data().children.values()
+    }
+
+    /// The definition an alt-object wraps; `None` for every other kind.
+    pub fn sub_object(&self) -> Option<&Object> {
         match self {
-            Object::Module(_) => "mod",
-            Object::Class(_) => "class",
-            Object::Function(_) => "func",
-            Object::AltObject(a) => a.sub_ob.ob_type(),
+            Object::AltObject(a) => Some(&a.sub_ob),
+            _ => None,
         }
     }
 
-    fn _dump_tree(&self, level: usize) {
+    /// The comments within this object's span whose text contains one
+    /// of `keywords` (typically `TODO`, `FIXME`, ...), with their line
+    /// numbers. Reads the file the span points into.
+    pub fn debt_comments(&self, keywords: &[&str]) -> std::io::Result<Vec<(usize, String)>> {
+        let span = &self.data().span;
+        let comments = comments_in_file(span.path())?;
+        Ok(comments
+            .into_iter()
+            .filter(|(row, text)| {
+                *row >= span.start
+                    && *row <= span.end
+                    && keywords.iter().any(|kw| text.contains(kw))
+            })
+            .collect())
+    }
+
+    /// Walks this object and its descendants pre-order (children in
+    /// source order), but does not recurse into the children of objects
+    /// of kind `stop_kind` (one of `"module"`, `"class"`, `"function"`,
+    /// `"variable"`, `"alt"`). Stopping at functions yields an outline
+    /// without closures and other nested definitions. The starting
+    /// object is always included, whatever its kind.
+    pub fn walk_until(&self, stop_kind: &str) -> Vec<&Object> {
+        fn walk<'a>(ob: &'a Object, stop_kind: &str, out: &mut Vec<&'a Object>) {
+            out.push(ob);
+            if ob.ob_type() == stop_kind {
+                return;
+            }
+            let mut children: Vec<&Object> = ob.children().collect();
+            children.sort_by_key(|child| child.data().span.start);
+            for child in children {
+                walk(child, stop_kind, out);
+            }
+        }
+
+        let mut out = Vec::new();
+        walk(self, stop_kind, &mut out);
+        out
+    }
+
+    /// A plain JSON form of this object, mirroring the shape of the
+    /// `to_dict` methods on the Python classes: name, path, span, kind
+    /// and children.
+    #[cfg(feature = "serde")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        data_json(self.data(), self.kind().as_str())
+    }
+
+    /// Marks this object and all its descendants as defined under an
+    /// `if TYPE_CHECKING:` block.
+    fn set_type_checking_only(&mut self) {
+        if let Object::AltObject(a) = self {
+            a.sub_ob.set_type_checking_only();
+        }
+        let data = self.data_mut();
+        data.type_checking_only = true;
+        for child in data.children.values_mut() {
+            child.set_type_checking_only();
+        }
+    }
+
+    /// Records `path` as the owning module of this object and all its
+    /// descendants. Called once per file by [`ModuleCreator::create`];
+    /// nested modules keep the paths their own creators assigned.
+    fn set_module_path(&mut self, path: &ObjectPath) {
+        if let Object::AltObject(a) = self {
+            a.sub_ob.set_module_path(path);
+        }
+        let data = self.data_mut();
+        data.module_path = path.clone();
+        for child in data.children.values_mut() {
+            child.set_module_path(path);
+        }
+    }
+
+    /// Assigns CPython-style qualified names below this object.
+    /// `prefix` is the text to prepend to this object's own name: empty
+    /// at module top-level, `"Outer."` below a class, and
+    /// `"outer.<locals>."` below a function. An alt-object and the
+    /// definition it wraps share a qualname, since at runtime the later
+    /// definition simply rebinds the name.
+    fn set_qualname(&mut self, prefix: &str) {
+        let base = match self {
+            Object::AltObject(a) => a.sub_ob.data().name().to_string(),
+            _ => self.data().name().to_string(),
+        };
+        let qualname = format!("{}{}", prefix, base);
+        let wraps_function = matches!(self, Object::Function(_))
+            || matches!(self, Object::AltObject(a) if matches!(a.sub_ob.as_ref(), Object::Function(_)));
+        let child_prefix = if wraps_function {
+            format!("{}.<locals>.", qualname)
+        } else {
+            format!("{}.", qualname)
+        };
+        if let Object::AltObject(a) = self {
+            a.sub_ob.data_mut().qualname = qualname.clone();
+            for child in a.sub_ob.data_mut().children.values_mut() {
+                child.set_qualname(&child_prefix);
+            }
+        }
+        let data = self.data_mut();
+        data.qualname = qualname;
+        for child in data.children.values_mut() {
+            child.set_qualname(&child_prefix);
+        }
+    }
+
+    /// The CPython-style qualified name of this object. See
+    /// [`ObjectData::qualname`].
+    pub fn qualname(&self) -> &str {
+        self.data().qualname()
+    }
+
+    /// Records the nearest enclosing class of this object and all its
+    /// descendants. `current` is the class directly above this object,
+    /// or `None` outside any class. An alt-object and the definition
+    /// it wraps share an enclosing class.
+    fn set_enclosing_class(&mut self, current: Option<&ObjectPath>) {
+        let own_class = match self {
+            Object::Class(class) => Some(class.data.obj_path.clone()),
+            Object::AltObject(a) => match a.sub_ob.as_ref() {
+                Object::Class(class) => Some(class.data.obj_path.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let child_class = own_class.as_ref().or(current);
+        if let Object::AltObject(a) = self {
+            a.sub_ob.data_mut().enclosing_class = current.cloned();
+            for child in a.sub_ob.data_mut().children.values_mut() {
+                child.set_enclosing_class(child_class);
+            }
+        }
+        let data = self.data_mut();
+        data.enclosing_class = current.cloned();
+        for child in data.children.values_mut() {
+            child.set_enclosing_class(child_class);
+        }
+    }
+
+    /// The canonical path of the nearest enclosing class, for building
+    /// `ClassName.method` references; `None` for objects outside any
+    /// class. See [`ObjectData::enclosing_class`].
+    pub fn enclosing_class_path(&self) -> Option<&ObjectPath> {
+        self.data().enclosing_class()
+    }
+
+    /// Collects the children whose base name (alt `#N` suffix stripped)
+    /// is `name`. For an alt-object, the wrapped definition's children
+    /// are searched, since the wrapper itself has none.
+    fn children_with_base_name<'a>(&'a self, name: &str, out: &mut Vec<&'a Object>) {
+        if let Some(sub_ob) = self.sub_object() {
+            sub_ob.children_with_base_name(name, out);
+        }
+        for child in self.children() {
+            if alt_base_name(child.data().name()) == name {
+                out.push(child);
+            }
+        }
+    }
+
+    fn collect_functions<'a>(&'a self, out: &mut Vec<(&'a ObjectPath, &'a Function)>) {
+        match self {
+            Object::Function(f) => out.push((&f.data.obj_path, f)),
+            Object::AltObject(a) => {
+                if let Object::Function(f) = a.sub_ob.as_ref() {
+                    out.push((&a.data.obj_path, f));
+                }
+                for child in a.sub_ob.data().children.values() {
+                    child.collect_functions(out);
+                }
+            }
+            _ => {}
+        }
+        for child in self.data().children.values() {
+            child.collect_functions(out);
+        }
+    }
+
+    fn collect_classes<'a>(&'a self, out: &mut Vec<(&'a ObjectPath, &'a Class)>) {
+        match self {
+            Object::Class(c) => out.push((&c.data.obj_path, c)),
+            Object::AltObject(a) => {
+                if let Object::Class(c) = a.sub_ob.as_ref() {
+                    out.push((&a.data.obj_path, c));
+                }
+                for child in a.sub_ob.data().children.values() {
+                    child.collect_classes(out);
+                }
+            }
+            _ => {}
+        }
+        for child in self.data().children.values() {
+            child.collect_classes(out);
+        }
+    }
+
+    /// The canonical kind of this object. An alt-object is its own
+    /// kind; use [`Object::sub_object`] to see what it wraps.
+    pub fn kind(&self) -> ObjectKind {
+        match self {
+            Object::Module(_) => ObjectKind::Module,
+            Object::Class(_) => ObjectKind::Class,
+            Object::Function(_) => ObjectKind::Function,
+            Object::Variable(_) => ObjectKind::Variable,
+            Object::AltObject(_) => ObjectKind::Alt,
+        }
+    }
+
+    pub fn ob_type(&self) -> &'static str {
+        self.kind().as_str()
+    }
+
+    fn dump_line(&self, level: usize) {
         let padding = "  ".repeat(level);
         println!(
             "{}{} ({}) => {}:{}",
@@ -414,14 +2119,36 @@ impl Object {
             self.data().span.path.display(),
             self.data().span.start
         );
+    }
+
+    fn _dump_tree(&self, level: usize) {
+        self.dump_line(level);
         for child in self.data().children.values() {
             child._dump_tree(level + 1);
         }
     }
 
+    fn _dump_tree_sorted(&self, level: usize) {
+        self.dump_line(level);
+        let mut children: Vec<&Object> = self.data().children.values().collect();
+        children.sort_by_key(|child| child.data().name());
+        for child in children {
+            child._dump_tree_sorted(level + 1);
+        }
+    }
+
     pub fn dump_tree(&self) {
         self._dump_tree(0)
     }
+
+    /// Like [`Object::dump_tree`], but with children printed in name
+    /// order at every level. `children` is a `HashMap`, so the plain
+    /// dump is nondeterministic; this variant is the one to use for
+    /// golden-file output. [`Object::walk_until`] already orders by
+    /// span start and needs no such switch.
+    pub fn dump_tree_sorted(&self) {
+        self._dump_tree_sorted(0)
+    }
 }
 
 impl PartialEq for Object {
@@ -439,10 +2166,19 @@ impl Hash for Object {
     }
 }
 
+/// A callback deriving a module's name from its file path, for layouts
+/// the default rule does not fit. Shared and called across threads
+/// during a parallel scan, hence the bounds.
+pub type ModNamer = Arc<dyn Fn(&Path) -> String + Send + Sync>;
+
 pub struct ModuleCreator {
     filename: PathBuf,
     line_cnt: usize,
     par_path: ObjectPath,
+    src_lines: Option<Vec<String>>,
+    mod_namer: Option<ModNamer>,
+    max_body_lines: Option<usize>,
+    source_root: Option<String>,
 }
 
 impl ModuleCreator {
@@ -451,25 +2187,92 @@ impl ModuleCreator {
             filename,
             line_cnt,
             par_path,
+            src_lines: None,
+            mod_namer: None,
+            max_body_lines: None,
+            source_root: None,
         }
     }
 
+    /// Makes the creator capture the verbatim source text of each decorator
+    /// (including whitespace and comments) on the created objects.
+    /// `source` must be the text of the file being parsed.
+    pub fn capture_decorator_source(mut self, source: &str) -> Self {
+        self.src_lines = Some(source.lines().map(str::to_string).collect());
+        self
+    }
+
+    /// Overrides how the module's name is derived from its file name.
+    /// The default maps `__init__.py` to the parent directory and
+    /// strips the extension from every other file.
+    pub fn with_mod_namer(mut self, namer: ModNamer) -> Self {
+        self.mod_namer = Some(namer);
+        self
+    }
+
+    /// Caps per-function statement extraction: a function whose span
+    /// exceeds `limit` lines keeps its signature and nested definitions
+    /// but gets no statements, and is marked
+    /// [`Function::body_truncated`]. Keeps generated files with
+    /// enormous bodies from dominating memory.
+    pub fn with_max_body_lines(mut self, limit: usize) -> Self {
+        self.max_body_lines = Some(limit);
+        self
+    }
+
+    /// Names a source-root directory (`src` in the common src layout)
+    /// whose leading component is stripped from every dotted path, so
+    /// `src/mypkg/mod.py` yields `mypkg.mod` rather than
+    /// `src.mypkg.mod`. The root module itself keeps its name.
+    pub fn with_source_root(mut self, name: String) -> Self {
+        self.source_root = Some(name);
+        self
+    }
+
     pub fn create(self, stmts: Vec<Stmt>) -> Module {
         let mod_path = self.mod_path();
-        let children = objects_from_stmts(stmts, &mod_path, &self.filename);
+        let mut imports = Vec::new();
+        collect_imports(&stmts, &mut imports);
+        let dunder_all = collect_dunder_all(&stmts);
+        let children = objects_from_stmts(
+            stmts,
+            &mod_path,
+            &self.filename,
+            self.src_lines.as_deref(),
+            false,
+            self.max_body_lines,
+        );
         let mod_span = SourceSpan::new(self.filename, 0, self.line_cnt);
-        let mut mod_data = ObjectData::new(mod_span, mod_path);
+        let mut mod_data = ObjectData::new(mod_span, mod_path.clone());
         mod_data.append_children(children);
-        Module { data: mod_data }
+        mod_data.module_path = mod_path.clone();
+        for child in mod_data.children.values_mut() {
+            child.set_module_path(&mod_path);
+            child.set_qualname("");
+            child.set_enclosing_class(None);
+        }
+        Module {
+            data: mod_data,
+            line_map: None,
+            parse_status: ParseStatus::Ok,
+            imports,
+            dunder_all,
+        }
     }
 
     fn mod_path(&self) -> ObjectPath {
         let mut mod_path = self.par_path.clone();
         mod_path.append_part(self.mod_name());
+        if let Some(root) = &self.source_root {
+            mod_path.strip_root(root);
+        }
         mod_path
     }
 
     fn mod_name(&self) -> String {
+        if let Some(namer) = &self.mod_namer {
+            return namer(&self.filename);
+        }
         let mut parts = self.filename.components().rev();
         let last = parts.next().unwrap();
         let name = if let Component::Normal(last) = last {
@@ -488,12 +2291,786 @@ impl ModuleCreator {
         };
         if let Some(name) = name.strip_suffix(".py") {
             name.to_string()
+        } else if let Some(name) = name.strip_suffix(".pyw") {
+            name.to_string()
         } else {
             name
         }
     }
 }
 
+/// All comments in `path` with their line numbers. Goes through the
+/// lexer, so a `#` inside a string literal is not mistaken for a
+/// comment. The leading `#` is part of the returned text.
+pub fn comments_in_file(path: &Path) -> std::io::Result<Vec<(usize, String)>> {
+    let code = std::fs::read_to_string(path)?;
+    let mut comments = Vec::new();
+    for tok in rustpython_parser::lexer::make_tokenizer(&code).flatten() {
+        let (start, tok, _) = tok;
+        if let Tok::Comment(text) = tok {
+            comments.push((start.row(), text));
+        }
+    }
+    Ok(comments)
+}
+
+/// A lexical token: its coarse kind, its verbatim source text, and its
+/// `(row, column)` start and end positions (rows 1-based, columns
+/// 0-based, matching the lexer).
+pub type LexToken = (String, String, (usize, usize), (usize, usize));
+
+/// Lexes `path` into [`LexToken`]s. See [`tokenize_source`].
+pub fn tokenize_file(path: &Path) -> std::io::Result<Vec<LexToken>> {
+    let code = std::fs::read_to_string(path)?;
+    Ok(tokenize_source(&code))
+}
+
+/// Lexes `code` into [`LexToken`]s, for consumers that need lexical
+/// detail the object model discards (highlighting, exact operator
+/// positions). Kinds are coarse: `"name"`, `"number"`, `"string"`,
+/// `"keyword"`, `"op"`, `"comment"`, `"newline"`, `"indent"`,
+/// `"dedent"` and `"eof"`. Tokens the lexer could not produce (after a
+/// lexical error) are simply absent, like [`comments_in_file`] does.
+pub fn tokenize_source(code: &str) -> Vec<LexToken> {
+    let lines: Vec<&str> = code.split('\n').collect();
+    let slice_line = |row: usize, from: usize, to: Option<usize>| -> String {
+        let Some(line) = lines.get(row - 1) else {
+            return String::new();
+        };
+        let to = to.unwrap_or(usize::MAX);
+        line.chars().take(to).skip(from).collect()
+    };
+    let mut tokens = Vec::new();
+    for (start, tok, end) in rustpython_parser::lexer::make_tokenizer(code).flatten() {
+        let text = if start.row() == end.row() {
+            slice_line(start.row(), start.column(), Some(end.column()))
+        } else {
+            let mut parts = vec![slice_line(start.row(), start.column(), None)];
+            for row in start.row() + 1..end.row() {
+                parts.push(slice_line(row, 0, None));
+            }
+            parts.push(slice_line(end.row(), 0, Some(end.column())));
+            parts.join("\n")
+        };
+        tokens.push((
+            tok_kind(&tok).to_string(),
+            text,
+            (start.row(), start.column()),
+            (end.row(), end.column()),
+        ));
+    }
+    tokens
+}
+
+fn tok_kind(tok: &Tok) -> &'static str {
+    use Tok::*;
+    match tok {
+        Name { .. } => "name",
+        Int { .. } | Float { .. } | Complex { .. } => "number",
+        String { .. } => "string",
+        Comment(_) => "comment",
+        Newline => "newline",
+        Indent => "indent",
+        Dedent => "dedent",
+        StartModule | StartInteractive | StartExpression => "start",
+        EndOfFile => "eof",
+        False | None | True | And | As | Assert | Async | Await | Break | Class | Continue
+        | Def | Del | Elif | Else | Except | Finally | For | From | Global | If | Import | In
+        | Is | Lambda | Nonlocal | Not | Or | Pass | Raise | Return | Try | While | With
+        | Yield => "keyword",
+        _ => "op",
+    }
+}
+
+/// Renders an expression like [`render_expr`], but never lets a
+/// rendering failure escape: should the renderer ever panic on a
+/// construct it cannot handle, the placeholder `<unrenderable>` is
+/// substituted and a warning printed instead. Decorator and annotation
+/// rendering during a parse goes through this, so one exotic expression
+/// cannot abort a whole [`crate::project::Project::create`].
+pub fn try_render_expr(kind: &ExprKind) -> String {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| render_expr(kind))).unwrap_or_else(
+        |_| {
+            eprintln!("warning: could not render expression, substituting a placeholder");
+            "<unrenderable>".to_string()
+        },
+    )
+}
+
+/// Renders an expression back to readable Python source text.
+/// Covers all expression kinds (names, attributes, calls, subscripts,
+/// constants, containers, comprehensions, operators, f-strings, ...),
+/// inserting parentheses according to operator precedence. All the
+/// "rendered string" features in the crate go through this function.
+pub fn render_expr(kind: &ExprKind) -> String {
+    render_expr_prec(kind, 0)
+}
+
+/// Renders `kind`, parenthesizing it when it binds less tightly than the
+/// surrounding context (`min_prec`) requires.
+fn render_expr_prec(kind: &ExprKind, min_prec: u8) -> String {
+    let rendered = render_expr_raw(kind);
+    if expr_prec(kind) < min_prec {
+        format!("({})", rendered)
+    } else {
+        rendered
+    }
+}
+
+/// Precedence levels loosely following the Python reference grammar;
+/// a bigger number binds tighter.
+fn expr_prec(kind: &ExprKind) -> u8 {
+    match kind {
+        ExprKind::NamedExpr { .. } | ExprKind::Yield { .. } | ExprKind::YieldFrom { .. } => 0,
+        ExprKind::Lambda { .. } => 1,
+        ExprKind::IfExp { .. } => 2,
+        ExprKind::BoolOp { op: Boolop::Or, .. } => 3,
+        ExprKind::BoolOp {
+            op: Boolop::And, ..
+        } => 4,
+        ExprKind::UnaryOp {
+            op: Unaryop::Not, ..
+        } => 5,
+        ExprKind::Compare { .. } => 6,
+        ExprKind::BinOp { op, .. } => operator_prec(op),
+        ExprKind::UnaryOp { .. } => 13,
+        ExprKind::Await { .. } => 15,
+        ExprKind::Attribute { .. } | ExprKind::Subscript { .. } | ExprKind::Call { .. } => 16,
+        // Only legal directly inside a subscript, never parenthesized there.
+        ExprKind::Slice { .. } => 17,
+        _ => 17,
+    }
+}
+
+fn operator_prec(op: &Operator) -> u8 {
+    match op {
+        Operator::BitOr => 7,
+        Operator::BitXor => 8,
+        Operator::BitAnd => 9,
+        Operator::LShift | Operator::RShift => 10,
+        Operator::Add | Operator::Sub => 11,
+        Operator::Mult | Operator::MatMult | Operator::Div | Operator::Mod | Operator::FloorDiv => {
+            12
+        }
+        Operator::Pow => 14,
+    }
+}
+
+fn operator_str(op: &Operator) -> &'static str {
+    match op {
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mult => "*",
+        Operator::MatMult => "@",
+        Operator::Div => "/",
+        Operator::Mod => "%",
+        Operator::Pow => "**",
+        Operator::LShift => "<<",
+        Operator::RShift => ">>",
+        Operator::BitOr => "|",
+        Operator::BitXor => "^",
+        Operator::BitAnd => "&",
+        Operator::FloorDiv => "//",
+    }
+}
+
+fn cmp_op_str(op: &Cmpop) -> &'static str {
+    match op {
+        Cmpop::Eq => "==",
+        Cmpop::NotEq => "!=",
+        Cmpop::Lt => "<",
+        Cmpop::LtE => "<=",
+        Cmpop::Gt => ">",
+        Cmpop::GtE => ">=",
+        Cmpop::Is => "is",
+        Cmpop::IsNot => "is not",
+        Cmpop::In => "in",
+        Cmpop::NotIn => "not in",
+    }
+}
+
+fn render_comprehensions(generators: &[Comprehension]) -> String {
+    let mut out = String::new();
+    for c in generators {
+        if c.is_async != 0 {
+            out.push_str(" async");
+        }
+        out.push_str(" for ");
+        out.push_str(&render_expr(&c.target.node));
+        out.push_str(" in ");
+        out.push_str(&render_expr_prec(&c.iter.node, 3));
+        for cond in &c.ifs {
+            out.push_str(" if ");
+            out.push_str(&render_expr_prec(&cond.node, 3));
+        }
+    }
+    out
+}
+
+fn render_lambda_args(args: &Arguments) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    parts.extend(args.posonlyargs.iter().map(|a| a.node.arg.clone()));
+    if !args.posonlyargs.is_empty() {
+        parts.push("/".to_string());
+    }
+    parts.extend(args.args.iter().map(|a| a.node.arg.clone()));
+    if let Some(vararg) = &args.vararg {
+        parts.push(format!("*{}", vararg.node.arg));
+    } else if !args.kwonlyargs.is_empty() {
+        parts.push("*".to_string());
+    }
+    parts.extend(args.kwonlyargs.iter().map(|a| a.node.arg.clone()));
+    if let Some(kwarg) = &args.kwarg {
+        parts.push(format!("**{}", kwarg.node.arg));
+    }
+    parts.join(", ")
+}
+
+fn render_expr_raw(kind: &ExprKind) -> String {
+    let prec = expr_prec(kind);
+    match kind {
+        ExprKind::BoolOp { op, values } => {
+            let sep = match op {
+                Boolop::And => " and ",
+                Boolop::Or => " or ",
+            };
+            let parts: Vec<String> = values
+                .iter()
+                .map(|v| render_expr_prec(&v.node, prec + 1))
+                .collect();
+            parts.join(sep)
+        }
+        ExprKind::NamedExpr { target, value } => format!(
+            "{} := {}",
+            render_expr_prec(&target.node, 17),
+            render_expr_prec(&value.node, 1)
+        ),
+        ExprKind::BinOp { left, op, right } => {
+            // `**` is right-associative, every other operator is
+            // left-associative.
+            let (lp, rp) = if matches!(op, Operator::Pow) {
+                (prec + 1, prec)
+            } else {
+                (prec, prec + 1)
+            };
+            format!(
+                "{} {} {}",
+                render_expr_prec(&left.node, lp),
+                operator_str(op),
+                render_expr_prec(&right.node, rp)
+            )
+        }
+        ExprKind::UnaryOp { op, operand } => {
+            let (sym, min) = match op {
+                Unaryop::Invert => ("~", 13),
+                Unaryop::Not => ("not ", 5),
+                Unaryop::UAdd => ("+", 13),
+                Unaryop::USub => ("-", 13),
+            };
+            format!("{}{}", sym, render_expr_prec(&operand.node, min))
+        }
+        ExprKind::Lambda { args, body } => {
+            let args = render_lambda_args(args);
+            if args.is_empty() {
+                format!("lambda: {}", render_expr_prec(&body.node, 2))
+            } else {
+                format!("lambda {}: {}", args, render_expr_prec(&body.node, 2))
+            }
+        }
+        ExprKind::IfExp { test, body, orelse } => format!(
+            "{} if {} else {}",
+            render_expr_prec(&body.node, 3),
+            render_expr_prec(&test.node, 3),
+            render_expr_prec(&orelse.node, 2)
+        ),
+        ExprKind::Dict { keys, values } => {
+            let mut out = String::from("{");
+            for (i, (k, v)) in keys.iter().zip(values).enumerate() {
+                if i != 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&render_expr(&k.node));
+                out.push_str(": ");
+                out.push_str(&render_expr(&v.node));
+            }
+            out.push('}');
+            out
+        }
+        ExprKind::Set { elts } => format!("{{{}}}", render_expr_list(elts)),
+        ExprKind::ListComp { elt, generators } => format!(
+            "[{}{}]",
+            render_expr(&elt.node),
+            render_comprehensions(generators)
+        ),
+        ExprKind::SetComp { elt, generators } => format!(
+            "{{{}{}}}",
+            render_expr(&elt.node),
+            render_comprehensions(generators)
+        ),
+        ExprKind::DictComp {
+            key,
+            value,
+            generators,
+        } => format!(
+            "{{{}: {}{}}}",
+            render_expr(&key.node),
+            render_expr(&value.node),
+            render_comprehensions(generators)
+        ),
+        ExprKind::GeneratorExp { elt, generators } => format!(
+            "({}{})",
+            render_expr(&elt.node),
+            render_comprehensions(generators)
+        ),
+        ExprKind::Await { value } => format!("await {}", render_expr_prec(&value.node, 16)),
+        ExprKind::Yield { value } => match value {
+            Some(value) => format!("yield {}", render_expr_prec(&value.node, 1)),
+            None => "yield".to_string(),
+        },
+        ExprKind::YieldFrom { value } => {
+            format!("yield from {}", render_expr_prec(&value.node, 1))
+        }
+        ExprKind::Compare {
+            left,
+            ops,
+            comparators,
+        } => {
+            let mut out = render_expr_prec(&left.node, prec + 1);
+            for (op, comp) in ops.iter().zip(comparators) {
+                out.push(' ');
+                out.push_str(cmp_op_str(op));
+                out.push(' ');
+                out.push_str(&render_expr_prec(&comp.node, prec + 1));
+            }
+            out
+        }
+        ExprKind::Call {
+            func,
+            args,
+            keywords,
+        } => {
+            let mut parts: Vec<String> = args.iter().map(|a| render_expr(&a.node)).collect();
+            for kw in keywords {
+                let value = render_expr(&kw.node.value.node);
+                match &kw.node.arg {
+                    Some(name) => parts.push(format!("{}={}", name, value)),
+                    None => parts.push(format!("**{}", value)),
+                }
+            }
+            format!("{}({})", render_expr_prec(&func.node, 16), parts.join(", "))
+        }
+        ExprKind::FormattedValue { value, .. } => {
+            format!("{{{}}}", render_expr(&value.node))
+        }
+        ExprKind::JoinedStr { values } => {
+            let mut out = String::from("f\"");
+            for value in values {
+                match &value.node {
+                    ExprKind::Constant {
+                        value: Constant::Str(s),
+                        ..
+                    } => out.push_str(s),
+                    other => out.push_str(&render_expr(other)),
+                }
+            }
+            out.push('"');
+            out
+        }
+        ExprKind::Constant { value, .. } => render_constant(value),
+        ExprKind::Attribute { value, attr, .. } => {
+            format!("{}.{}", render_expr_prec(&value.node, 16), attr)
+        }
+        ExprKind::Subscript { value, slice, .. } => {
+            // A tuple subscript is written without its parentheses,
+            // as in `Dict[str, int]` or `a[1:2, 3]`.
+            let slice_str = match &slice.node {
+                ExprKind::Tuple { elts, .. } => render_expr_list(elts),
+                other => render_expr(other),
+            };
+            format!("{}[{}]", render_expr_prec(&value.node, 16), slice_str)
+        }
+        ExprKind::Starred { value, .. } => {
+            format!("*{}", render_expr_prec(&value.node, 16))
+        }
+        ExprKind::Name { id, .. } => id.clone(),
+        ExprKind::List { elts, .. } => format!("[{}]", render_expr_list(elts)),
+        ExprKind::Tuple { elts, .. } => {
+            if elts.len() == 1 {
+                format!("({},)", render_expr(&elts[0].node))
+            } else {
+                format!("({})", render_expr_list(elts))
+            }
+        }
+        ExprKind::Slice { lower, upper, step } => {
+            let part = |e: &Option<Box<Expr>>| {
+                e.as_ref().map(|e| render_expr(&e.node)).unwrap_or_default()
+            };
+            match step {
+                Some(step) => format!(
+                    "{}:{}:{}",
+                    part(lower),
+                    part(upper),
+                    render_expr(&step.node)
+                ),
+                None => format!("{}:{}", part(lower), part(upper)),
+            }
+        }
+    }
+}
+
+fn render_expr_list(elts: &[Expr]) -> String {
+    let mut out = String::new();
+    for (i, elt) in elts.iter().enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&render_expr(&elt.node));
+    }
+    out
+}
+
+fn render_constant(value: &Constant) -> String {
+    match value {
+        Constant::None => "None".to_string(),
+        Constant::Bool(true) => "True".to_string(),
+        Constant::Bool(false) => "False".to_string(),
+        Constant::Str(s) => format!("{:?}", s),
+        Constant::Bytes(b) => format!("b\"{}\"", b.escape_ascii()),
+        Constant::Int(i) => i.to_string(),
+        Constant::Float(f) => f.to_string(),
+        Constant::Complex { real, imag } => format!("({}+{}j)", real, imag),
+        Constant::Tuple(t) => {
+            let mut out = String::from("(");
+            for (i, c) in t.iter().enumerate() {
+                if i != 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&render_constant(c));
+            }
+            out.push(')');
+            out
+        }
+        Constant::Ellipsis => "...".to_string(),
+    }
+}
+
+/// Renders a statement back to a single line of Python source text.
+/// Compound statements render as their header only (`if test:`,
+/// `for x in xs:`, ...): the flattened statement maps already list their
+/// bodies separately, so the header carries all the information the
+/// statement itself adds.
+pub fn render_stmt(kind: &StmtKind) -> String {
+    match kind {
+        StmtKind::FunctionDef { name, args, .. } => {
+            format!("def {}({}):", name, render_lambda_args(args))
+        }
+        StmtKind::AsyncFunctionDef { name, args, .. } => {
+            format!("async def {}({}):", name, render_lambda_args(args))
+        }
+        StmtKind::ClassDef { name, bases, .. } => {
+            if bases.is_empty() {
+                format!("class {}:", name)
+            } else {
+                format!("class {}({}):", name, render_expr_list(bases))
+            }
+        }
+        StmtKind::Return { value } => match value {
+            Some(value) => format!("return {}", render_expr(&value.node)),
+            None => "return".to_string(),
+        },
+        StmtKind::Delete { targets } => format!("del {}", render_expr_list(targets)),
+        StmtKind::Assign { targets, value, .. } => {
+            let mut out = String::new();
+            for target in targets {
+                out.push_str(&render_expr(&target.node));
+                out.push_str(" = ");
+            }
+            out.push_str(&render_expr(&value.node));
+            out
+        }
+        StmtKind::AugAssign { target, op, value } => format!(
+            "{} {}= {}",
+            render_expr(&target.node),
+            operator_str(op),
+            render_expr(&value.node)
+        ),
+        StmtKind::AnnAssign {
+            target,
+            annotation,
+            value,
+            ..
+        } => {
+            let mut out = format!(
+                "{}: {}",
+                render_expr(&target.node),
+                render_expr(&annotation.node)
+            );
+            if let Some(value) = value {
+                out.push_str(" = ");
+                out.push_str(&render_expr(&value.node));
+            }
+            out
+        }
+        StmtKind::For { target, iter, .. } => format!(
+            "for {} in {}:",
+            render_expr(&target.node),
+            render_expr(&iter.node)
+        ),
+        StmtKind::AsyncFor { target, iter, .. } => format!(
+            "async for {} in {}:",
+            render_expr(&target.node),
+            render_expr(&iter.node)
+        ),
+        StmtKind::While { test, .. } => format!("while {}:", render_expr(&test.node)),
+        StmtKind::If { test, .. } => format!("if {}:", render_expr(&test.node)),
+        StmtKind::With { items, .. } | StmtKind::AsyncWith { items, .. } => {
+            let mut out = String::new();
+            if matches!(kind, StmtKind::AsyncWith { .. }) {
+                out.push_str("async ");
+            }
+            out.push_str("with ");
+            for (i, item) in items.iter().enumerate() {
+                if i != 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&render_expr(&item.context_expr.node));
+                if let Some(vars) = &item.optional_vars {
+                    out.push_str(" as ");
+                    out.push_str(&render_expr(&vars.node));
+                }
+            }
+            out.push(':');
+            out
+        }
+        StmtKind::Match { subject, .. } => format!("match {}:", render_expr(&subject.node)),
+        StmtKind::Raise { exc, cause } => {
+            let mut out = String::from("raise");
+            if let Some(exc) = exc {
+                out.push(' ');
+                out.push_str(&render_expr(&exc.node));
+            }
+            if let Some(cause) = cause {
+                out.push_str(" from ");
+                out.push_str(&render_expr(&cause.node));
+            }
+            out
+        }
+        StmtKind::Try { .. } => "try:".to_string(),
+        StmtKind::Assert { test, msg } => {
+            let mut out = format!("assert {}", render_expr(&test.node));
+            if let Some(msg) = msg {
+                out.push_str(", ");
+                out.push_str(&render_expr(&msg.node));
+            }
+            out
+        }
+        StmtKind::Import { names } => {
+            let parts: Vec<String> = names.iter().map(|a| render_alias(&a.node)).collect();
+            format!("import {}", parts.join(", "))
+        }
+        StmtKind::ImportFrom {
+            module,
+            names,
+            level,
+        } => {
+            let dots = ".".repeat(level.unwrap_or(0));
+            let parts: Vec<String> = names.iter().map(|a| render_alias(&a.node)).collect();
+            format!(
+                "from {}{} import {}",
+                dots,
+                module.as_deref().unwrap_or(""),
+                parts.join(", ")
+            )
+        }
+        StmtKind::Global { names } => format!("global {}", names.join(", ")),
+        StmtKind::Nonlocal { names } => format!("nonlocal {}", names.join(", ")),
+        StmtKind::Expr { value } => render_expr(&value.node),
+        StmtKind::Pass => "pass".to_string(),
+        StmtKind::Break => "break".to_string(),
+        StmtKind::Continue => "continue".to_string(),
+    }
+}
+
+fn render_alias(alias: &AliasData) -> String {
+    match &alias.asname {
+        Some(asname) => format!("{} as {}", alias.name, asname),
+        None => alias.name.clone(),
+    }
+}
+
+/// Calls `f` on the expressions appearing directly in `kind`, and on all
+/// their subexpressions. Statements nested in a compound statement's body
+/// are *not* descended into: the flattened statement maps built by
+/// [`extract_statement`] already list those separately.
+fn visit_stmt_exprs(kind: &StmtKind, f: &mut dyn FnMut(&Expr)) {
+    let visit_opt = |e: &Option<Box<Expr>>, f: &mut dyn FnMut(&Expr)| {
+        if let Some(e) = e {
+            visit_expr(e, f);
+        }
+    };
+    match kind {
+        StmtKind::Return { value } => visit_opt(value, f),
+        StmtKind::Delete { targets } => targets.iter().for_each(|e| visit_expr(e, f)),
+        StmtKind::Assign { targets, value, .. } => {
+            targets.iter().for_each(|e| visit_expr(e, f));
+            visit_expr(value, f);
+        }
+        StmtKind::AugAssign { target, value, .. } => {
+            visit_expr(target, f);
+            visit_expr(value, f);
+        }
+        StmtKind::AnnAssign {
+            target,
+            annotation,
+            value,
+            ..
+        } => {
+            visit_expr(target, f);
+            visit_expr(annotation, f);
+            visit_opt(value, f);
+        }
+        StmtKind::For { target, iter, .. } | StmtKind::AsyncFor { target, iter, .. } => {
+            visit_expr(target, f);
+            visit_expr(iter, f);
+        }
+        StmtKind::While { test, .. } | StmtKind::If { test, .. } => visit_expr(test, f),
+        StmtKind::With { items, .. } | StmtKind::AsyncWith { items, .. } => {
+            for item in items {
+                visit_expr(&item.context_expr, f);
+                if let Some(vars) = &item.optional_vars {
+                    visit_expr(vars, f);
+                }
+            }
+        }
+        StmtKind::Match { subject, cases } => {
+            visit_expr(subject, f);
+            for case in cases {
+                if let Some(guard) = &case.guard {
+                    visit_expr(guard, f);
+                }
+            }
+        }
+        StmtKind::Raise { exc, cause } => {
+            visit_opt(exc, f);
+            visit_opt(cause, f);
+        }
+        StmtKind::Try { handlers, .. } => {
+            for handler in handlers {
+                match &handler.node {
+                    ExcepthandlerKind::ExceptHandler { type_, .. } => visit_opt(type_, f),
+                }
+            }
+        }
+        StmtKind::Assert { test, msg } => {
+            visit_expr(test, f);
+            visit_opt(msg, f);
+        }
+        StmtKind::Expr { value } => visit_expr(value, f),
+        _ => {}
+    }
+}
+
+/// Calls `f` on `expr` and every expression nested within it.
+fn visit_expr(expr: &Expr, f: &mut dyn FnMut(&Expr)) {
+    f(expr);
+    let visit_vec = |exprs: &[Expr], f: &mut dyn FnMut(&Expr)| {
+        exprs.iter().for_each(|e| visit_expr(e, f));
+    };
+    let visit_opt = |e: &Option<Box<Expr>>, f: &mut dyn FnMut(&Expr)| {
+        if let Some(e) = e {
+            visit_expr(e, f);
+        }
+    };
+    match &expr.node {
+        ExprKind::BoolOp { values, .. } => visit_vec(values, f),
+        ExprKind::NamedExpr { target, value } => {
+            visit_expr(target, f);
+            visit_expr(value, f);
+        }
+        ExprKind::BinOp { left, right, .. } => {
+            visit_expr(left, f);
+            visit_expr(right, f);
+        }
+        ExprKind::UnaryOp { operand, .. } => visit_expr(operand, f),
+        ExprKind::Lambda { args, body } => {
+            visit_vec(&args.defaults, f);
+            visit_vec(&args.kw_defaults, f);
+            visit_expr(body, f);
+        }
+        ExprKind::IfExp { test, body, orelse } => {
+            visit_expr(test, f);
+            visit_expr(body, f);
+            visit_expr(orelse, f);
+        }
+        ExprKind::Dict { keys, values } => {
+            visit_vec(keys, f);
+            visit_vec(values, f);
+        }
+        ExprKind::Set { elts } => visit_vec(elts, f),
+        ExprKind::ListComp { elt, generators }
+        | ExprKind::SetComp { elt, generators }
+        | ExprKind::GeneratorExp { elt, generators } => {
+            visit_expr(elt, f);
+            for c in generators {
+                visit_expr(&c.target, f);
+                visit_expr(&c.iter, f);
+                visit_vec(&c.ifs, f);
+            }
+        }
+        ExprKind::DictComp {
+            key,
+            value,
+            generators,
+        } => {
+            visit_expr(key, f);
+            visit_expr(value, f);
+            for c in generators {
+                visit_expr(&c.target, f);
+                visit_expr(&c.iter, f);
+                visit_vec(&c.ifs, f);
+            }
+        }
+        ExprKind::Await { value } | ExprKind::YieldFrom { value } => visit_expr(value, f),
+        ExprKind::Yield { value } => visit_opt(value, f),
+        ExprKind::Compare {
+            left, comparators, ..
+        } => {
+            visit_expr(left, f);
+            visit_vec(comparators, f);
+        }
+        ExprKind::Call {
+            func,
+            args,
+            keywords,
+        } => {
+            visit_expr(func, f);
+            visit_vec(args, f);
+            for kw in keywords {
+                visit_expr(&kw.node.value, f);
+            }
+        }
+        ExprKind::FormattedValue {
+            value, format_spec, ..
+        } => {
+            visit_expr(value, f);
+            visit_opt(format_spec, f);
+        }
+        ExprKind::JoinedStr { values } => visit_vec(values, f),
+        ExprKind::Attribute { value, .. } | ExprKind::Starred { value, .. } => visit_expr(value, f),
+        ExprKind::Subscript { value, slice, .. } => {
+            visit_expr(value, f);
+            visit_expr(slice, f);
+        }
+        ExprKind::List { elts, .. } | ExprKind::Tuple { elts, .. } => visit_vec(elts, f),
+        ExprKind::Slice { lower, upper, step } => {
+            visit_opt(lower, f);
+            visit_opt(upper, f);
+            visit_opt(step, f);
+        }
+        ExprKind::Constant { .. } | ExprKind::Name { .. } => {}
+    }
+}
+
 fn extract_statements_from_body(stmts: Vec<Stmt>) -> HashMap<usize, StmtKind> {
     let mut stmts_map = HashMap::new();
     for stmt in stmts {
@@ -544,7 +3121,144 @@ fn extract_statement(stmt: Stmt) -> HashMap<usize, StmtKind> {
     stmts
 }
 
-fn objects_from_stmts(stmts: Vec<Stmt>, par_path: &ObjectPath, file_path: &Path) -> Vec<Object> {
+/// Extracts the verbatim source text of each decorator in `decorator_list`.
+/// A decorator's text runs from its own first line up to the line before the
+/// next decorator (or the `def`/`class` line itself), so interleaved comments
+/// are preserved.
+fn decorator_sources(
+    decorator_list: &[Expr],
+    def_row: usize,
+    src_lines: Option<&[String]>,
+) -> Vec<String> {
+    let Some(lines) = src_lines else {
+        return Vec::new();
+    };
+    let mut sources = Vec::new();
+    for (i, dec) in decorator_list.iter().enumerate() {
+        let start = dec.location.row();
+        let end = decorator_list
+            .get(i + 1)
+            .map(|d| d.location.row())
+            .unwrap_or(def_row);
+        let text = lines
+            .get(start - 1..end - 1)
+            .map(|ls| ls.join("\n"))
+            .unwrap_or_default();
+        sources.push(text);
+    }
+    sources
+}
+
+/// Collects the imports performed at module load time: `stmts` and its
+/// control-flow blocks and class bodies are walked, function bodies are
+/// not — a function-local import is deferred by design and does not
+/// couple modules when they load.
+fn collect_imports(stmts: &[Stmt], out: &mut Vec<ImportRecord>) {
+    for stmt in stmts {
+        match &stmt.node {
+            StmtKind::Import { names } => {
+                for alias in names {
+                    out.push(ImportRecord {
+                        module: alias.node.name.clone(),
+                        names: Vec::new(),
+                        level: 0,
+                    });
+                }
+            }
+            StmtKind::ImportFrom {
+                module,
+                names,
+                level,
+            } => out.push(ImportRecord {
+                module: module.clone().unwrap_or_default(),
+                names: names.iter().map(|a| a.node.name.clone()).collect(),
+                level: level.unwrap_or(0),
+            }),
+            StmtKind::ClassDef { body, .. } => collect_imports(body, out),
+            StmtKind::For { body, orelse, .. }
+            | StmtKind::AsyncFor { body, orelse, .. }
+            | StmtKind::While { body, orelse, .. }
+            | StmtKind::If { body, orelse, .. } => {
+                collect_imports(body, out);
+                collect_imports(orelse, out);
+            }
+            StmtKind::With { body, .. } | StmtKind::AsyncWith { body, .. } => {
+                collect_imports(body, out)
+            }
+            StmtKind::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                collect_imports(body, out);
+                collect_imports(orelse, out);
+                collect_imports(finalbody, out);
+                for h in handlers {
+                    let ExcepthandlerKind::ExceptHandler { body, .. } = &h.node;
+                    collect_imports(body, out);
+                }
+            }
+            StmtKind::Match { cases, .. } => {
+                for cs in cases {
+                    collect_imports(&cs.body, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Extracts a top-level `__all__ = [...]` (or tuple) of string
+/// literals; the last literal assignment wins. Computed forms and
+/// augmented assignments are left alone, yielding `None` unless a
+/// literal assignment also exists.
+fn collect_dunder_all(stmts: &[Stmt]) -> Option<Vec<String>> {
+    let mut result = None;
+    for stmt in stmts {
+        let (targets, value) = match &stmt.node {
+            StmtKind::Assign { targets, value, .. } => (targets.as_slice(), value),
+            StmtKind::AnnAssign {
+                target,
+                value: Some(value),
+                ..
+            } => (std::slice::from_ref(&**target), value),
+            _ => continue,
+        };
+        let named = targets
+            .iter()
+            .any(|t| matches!(&t.node, ExprKind::Name { id, .. } if id == "__all__"));
+        if !named {
+            continue;
+        }
+        let (ExprKind::List { elts, .. } | ExprKind::Tuple { elts, .. }) = &value.node else {
+            continue;
+        };
+        let names: Option<Vec<String>> = elts
+            .iter()
+            .map(|e| match &e.node {
+                ExprKind::Constant {
+                    value: Constant::Str(s),
+                    ..
+                } => Some(s.clone()),
+                _ => None,
+            })
+            .collect();
+        if let Some(names) = names {
+            result = Some(names);
+        }
+    }
+    result
+}
+
+fn objects_from_stmts(
+    stmts: Vec<Stmt>,
+    par_path: &ObjectPath,
+    file_path: &Path,
+    src_lines: Option<&[String]>,
+    in_class: bool,
+    max_body_lines: Option<usize>,
+) -> Vec<Object> {
     let make_span = |loc: Location, end_loc: Option<Location>| {
         let start = loc.row();
         let end = end_loc.unwrap().row();
@@ -560,56 +3274,190 @@ fn objects_from_stmts(stmts: Vec<Stmt>, par_path: &ObjectPath, file_path: &Path)
     for stmt in stmts {
         let kind = stmt.node;
         match kind {
-            StmtKind::ClassDef { name, body, .. } => {
+            StmtKind::ClassDef {
+                name,
+                bases,
+                body,
+                decorator_list,
+                ..
+            } => {
                 let class_path = make_path(name);
                 let class_span = make_span(stmt.location, stmt.end_location);
 
-                let children = objects_from_stmts(body, &class_path, file_path);
+                let children = objects_from_stmts(
+                    body,
+                    &class_path,
+                    file_path,
+                    src_lines,
+                    true,
+                    max_body_lines,
+                );
                 let mut class_data = ObjectData::new(class_span, class_path);
                 class_data.append_children(children);
-                let class = Class { data: class_data };
+                class_data.decorator_sources =
+                    decorator_sources(&decorator_list, stmt.location.row(), src_lines);
+                let mut class = Class {
+                    data: class_data,
+                    decorators: decorator_list,
+                    bases,
+                };
+                // Methods of a `TestCase` subclass carry the fact with
+                // them, so `Function::is_test` needs no tree context.
+                let is_testcase = class
+                    .base_names()
+                    .iter()
+                    .any(|base| base == "TestCase" || base.ends_with(".TestCase"));
+                if is_testcase {
+                    for child in class.data.children.values_mut() {
+                        match child {
+                            Object::Function(f) => f.in_testcase = true,
+                            Object::AltObject(a) => {
+                                if let Object::Function(f) = a.sub_ob.as_mut() {
+                                    f.in_testcase = true;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
                 objects.push(Object::Class(class));
             }
             StmtKind::FunctionDef {
-                name, args, body, ..
+                name,
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
             } => {
                 let func_path = make_path(name);
                 let func_span = make_span(stmt.location, stmt.end_location);
 
-                let children = objects_from_stmts(body.clone(), &func_path, file_path);
-                let stmts = extract_statements_from_body(body);
+                let children = objects_from_stmts(
+                    body.clone(),
+                    &func_path,
+                    file_path,
+                    src_lines,
+                    false,
+                    max_body_lines,
+                );
+                let body_truncated = max_body_lines
+                    .map(|limit| func_span.end - func_span.start + 1 > limit)
+                    .unwrap_or(false);
+                let (stmts, body) = if body_truncated {
+                    (HashMap::new(), Vec::new())
+                } else {
+                    (extract_statements_from_body(body.clone()), body)
+                };
                 let mut func_data = ObjectData::new(func_span, func_path);
                 func_data.append_children(children);
+                func_data.decorator_sources =
+                    decorator_sources(&decorator_list, stmt.location.row(), src_lines);
 
                 let func = Function {
                     data: func_data,
                     args: *args,
                     stmts,
+                    body,
+                    decorators: decorator_list,
+                    resolved_decorators: Vec::new(),
+                    returns,
+                    body_truncated,
+                    in_testcase: false,
                 };
                 objects.push(Object::Function(func));
             }
-            // TODO: Handle async function
-            StmtKind::For { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
-            }
-            StmtKind::AsyncFor { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
-            }
-            StmtKind::While { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
-            }
-            StmtKind::If { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
-            }
-            StmtKind::With { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
+            StmtKind::AnnAssign {
+                target,
+                annotation,
+                value,
+                simple,
+            } if in_class => {
+                // Class-scope annotated attributes (dataclass/attrs
+                // fields and the like) become Variable objects; only
+                // plain-name targets declare an attribute.
+                if let ExprKind::Name { id, .. } = &target.node {
+                    let var_path = make_path(id.clone());
+                    let var_span = make_span(stmt.location, stmt.end_location);
+                    let var = Variable {
+                        data: ObjectData::new(var_span, var_path),
+                        annotation: render_expr(&annotation.node),
+                        value: value.as_ref().map(|v| render_expr(&v.node)),
+                        simple: simple != 0,
+                    };
+                    objects.push(Object::Variable(var));
+                }
             }
-            StmtKind::AsyncWith { body, .. } => {
-                objects.extend(objects_from_stmts(body, par_path, file_path))
+            // TODO: Handle async function
+            StmtKind::For { body, .. } => objects.extend(objects_from_stmts(
+                body,
+                par_path,
+                file_path,
+                src_lines,
+                in_class,
+                max_body_lines,
+            )),
+            StmtKind::AsyncFor { body, .. } => objects.extend(objects_from_stmts(
+                body,
+                par_path,
+                file_path,
+                src_lines,
+                in_class,
+                max_body_lines,
+            )),
+            StmtKind::While { body, .. } => objects.extend(objects_from_stmts(
+                body,
+                par_path,
+                file_path,
+                src_lines,
+                in_class,
+                max_body_lines,
+            )),
+            StmtKind::If { test, body, .. } => {
+                let mut body_obs = objects_from_stmts(
+                    body,
+                    par_path,
+                    file_path,
+                    src_lines,
+                    in_class,
+                    max_body_lines,
+                );
+                // Definitions guarded by `if TYPE_CHECKING:` only exist
+                // for the benefit of type checkers; tag them as such.
+                let guard = render_expr(&test.node);
+                if guard == "TYPE_CHECKING" || guard == "typing.TYPE_CHECKING" {
+                    for ob in &mut body_obs {
+                        ob.set_type_checking_only();
+                    }
+                }
+                objects.extend(body_obs)
             }
+            StmtKind::With { body, .. } => objects.extend(objects_from_stmts(
+                body,
+                par_path,
+                file_path,
+                src_lines,
+                in_class,
+                max_body_lines,
+            )),
+            StmtKind::AsyncWith { body, .. } => objects.extend(objects_from_stmts(
+                body,
+                par_path,
+                file_path,
+                src_lines,
+                in_class,
+                max_body_lines,
+            )),
             StmtKind::Match { cases, .. } => {
                 for cs in cases {
-                    objects.extend(objects_from_stmts(cs.body, par_path, file_path));
+                    objects.extend(objects_from_stmts(
+                        cs.body,
+                        par_path,
+                        file_path,
+                        src_lines,
+                        in_class,
+                        max_body_lines,
+                    ));
                 }
             }
             StmtKind::Try {
@@ -619,12 +3467,26 @@ fn objects_from_stmts(stmts: Vec<Stmt>, par_path: &ObjectPath, file_path: &Path)
                 finalbody,
             } => {
                 for b in [body, orelse, finalbody] {
-                    objects.extend(objects_from_stmts(b, par_path, file_path));
+                    objects.extend(objects_from_stmts(
+                        b,
+                        par_path,
+                        file_path,
+                        src_lines,
+                        in_class,
+                        max_body_lines,
+                    ));
                 }
                 for h in handlers {
                     match h.node {
                         ExcepthandlerKind::ExceptHandler { body, .. } => {
-                            objects.extend(objects_from_stmts(body, par_path, file_path))
+                            objects.extend(objects_from_stmts(
+                                body,
+                                par_path,
+                                file_path,
+                                src_lines,
+                                in_class,
+                                max_body_lines,
+                            ))
                         }
                     }
                 }
@@ -634,3 +3496,23 @@ fn objects_from_stmts(stmts: Vec<Stmt>, par_path: &ObjectPath, file_path: &Path)
     }
     objects
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function_from_source(code: &str) -> Function {
+        let module = crate::project::Project::from_reader("file.py", code.as_bytes()).unwrap();
+        let func = module.into_children().into_iter().find_map(|ob| match ob {
+            Object::Function(func) => Some(func),
+            _ => None,
+        });
+        func.expect("source defines no top-level function")
+    }
+
+    #[test]
+    fn test_format_args_bare_star() {
+        let func = function_from_source("def f(a, *, b):\n    pass\n");
+        assert_eq!(func.format_args(), "a, *, b");
+    }
+}
@@ -5,10 +5,10 @@ use std::{
 
 use itertools::Itertools;
 use pyo3::{
-    exceptions::PyValueError,
+    exceptions::{PyKeyError, PyValueError},
     prelude::*,
     pyclass::CompareOp,
-    types::{PyComplex, PyTuple},
+    types::{PyComplex, PyDict, PyLong, PyTuple},
 };
 use rustpython_parser::ast::{
     Alias, Arg, Arguments, Boolop, Cmpop, Comprehension, Constant, Excepthandler,
@@ -94,25 +94,91 @@ pub struct Object {
     object_path: ObjectPath,
     children: HashMap<String, PyObject>,
     name: String,
+    module_path: String,
+    type_checking_only: bool,
+    /// The CPython-style `__qualname__` of this object: nesting joined
+    /// with `.`, with `<locals>` inserted below each enclosing
+    /// function. Empty for a module.
+    qualname: String,
+    /// A free-form payload slot for consumers: nothing in the parse
+    /// reads or writes it, it just travels with the object.
+    metadata: HashMap<String, PyObject>,
 }
 
 #[pymethods]
 impl Object {
     #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, children, module_path = "".to_string(),
+        type_checking_only = false, qualname = "".to_string()
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         source_span: SourceSpan,
         name: String,
         object_path: ObjectPath,
         children: HashMap<String, PyObject>,
+        module_path: String,
+        type_checking_only: bool,
+        qualname: String,
     ) -> Self {
         Self {
             source_span,
             object_path,
             name,
             children,
+            module_path,
+            type_checking_only,
+            qualname,
+            metadata: HashMap::new(),
         }
     }
 
+    /// Attaches an arbitrary value to this object under `key`. The
+    /// metadata plays no part in parsing; it is a slot for consumers to
+    /// decorate the tree without a side table.
+    fn set_meta(&mut self, key: String, value: PyObject) {
+        self.metadata.insert(key, value);
+    }
+
+    /// The value attached under `key` with `set_meta`, or `None`.
+    fn get_meta(&self, key: &str) -> Option<PyObject> {
+        self.metadata.get(key).cloned()
+    }
+
+    /// The file this object was defined in, from its span.
+    fn filename(&self) -> String {
+        self.source_span.filename.clone()
+    }
+
+    /// This object's file path relative to `root`, for display. The
+    /// path comes back unchanged when it is not under `root`.
+    fn relative_path(&self, root: String) -> String {
+        let path = std::path::Path::new(&self.source_span.filename);
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The comments within this object's span whose text contains one
+    /// of `keywords`, as `(line, text)` pairs. Reads the source file.
+    #[pyo3(signature = (
+        keywords = vec!["TODO".to_string(), "FIXME".to_string(), "XXX".to_string()]
+    ))]
+    fn debt_comments(&self, keywords: Vec<String>) -> PyResult<Vec<(usize, String)>> {
+        let comments =
+            crate::object::comments_in_file(std::path::Path::new(&self.source_span.filename))?;
+        Ok(comments
+            .into_iter()
+            .filter(|(row, text)| {
+                *row as i32 >= self.source_span.start_line
+                    && *row as i32 <= self.source_span.end_line
+                    && keywords.iter().any(|kw| text.contains(kw))
+            })
+            .collect())
+    }
+
     fn __str__(&self) -> String {
         unimplemented!("Object is a base-class, no str representation")
     }
@@ -134,6 +200,146 @@ impl Object {
     fn full_path(&self) -> ObjectPath {
         self.object_path.clone()
     }
+
+    /// Whether `name` names a direct child, with alternate definitions
+    /// (`foo#1`) counting under their base name: `"foo" in module`.
+    fn __contains__(&self, name: &str) -> bool {
+        self.children
+            .keys()
+            .any(|key| crate::object::alt_base_name(key) == name)
+    }
+
+    /// The direct child called `name`, or `default` when there is none,
+    /// mirroring dict `get` semantics. Returns the primary definition;
+    /// alternates stay reachable through the `children` dict.
+    #[pyo3(signature = (name, default = None))]
+    fn get(&self, name: &str, default: Option<PyObject>) -> Option<PyObject> {
+        self.children.get(name).cloned().or(default)
+    }
+
+    /// The direct child called `name`: `module["foo"]`. Always the
+    /// primary definition, even when alternates (`foo#1`) exist.
+    /// Raises `KeyError` when there is no such child.
+    fn __getitem__(&self, name: &str) -> PyResult<PyObject> {
+        self.children
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err(name.to_string()))
+    }
+
+    /// Walks this object and its descendants pre-order (children in
+    /// source order), but does not descend into the children of objects
+    /// whose kind matches `stop_kind` (`"module"`, `"class"`,
+    /// `"function"`, `"variable"` or `"alt"`). Stopping at functions
+    /// gives an outline that omits closures and other nested
+    /// definitions.
+    fn walk_until(self_: &PyCell<Self>, stop_kind: String) -> PyResult<Vec<PyObject>> {
+        fn walk(
+            py: Python<'_>,
+            ob: PyObject,
+            stop_kind: &str,
+            out: &mut Vec<PyObject>,
+        ) -> PyResult<()> {
+            out.push(ob.clone());
+            if py_kind(ob.as_ref(py)) == stop_kind {
+                return Ok(());
+            }
+            let children: HashMap<String, PyObject> =
+                ob.as_ref(py).getattr("children")?.extract()?;
+            let mut ordered = Vec::new();
+            for child in children.into_values() {
+                let start: i32 = child
+                    .as_ref(py)
+                    .getattr("source_span")?
+                    .getattr("start_line")?
+                    .extract()?;
+                ordered.push((start, child));
+            }
+            ordered.sort_by_key(|(start, _)| *start);
+            for (_, child) in ordered {
+                walk(py, child, stop_kind, out)?;
+            }
+            Ok(())
+        }
+
+        let py = self_.py();
+        let mut out = Vec::new();
+        walk(py, self_.into_py(py), &stop_kind, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// Walks `ob` and its descendants, collecting every object for which
+/// `pred` returns a truthy value.
+fn filter_objects_rec(
+    ob: &PyAny,
+    pred: &PyAny,
+    matches: &mut Vec<PyObject>,
+    py: Python<'_>,
+) -> PyResult<()> {
+    if pred.call1((ob,))?.is_true()? {
+        matches.push(ob.into_py(py));
+    }
+    let children: HashMap<String, PyObject> = ob.getattr("children")?.extract()?;
+    for child in children.values() {
+        filter_objects_rec(child.as_ref(py), pred, matches, py)?;
+    }
+    Ok(())
+}
+
+/// Collects the children of `ob` whose base name (alt `#N` suffix
+/// stripped) is `name`, looking through an alt-object's wrapped
+/// definition as well.
+fn children_with_base_name(ob: &PyAny, name: &str, out: &mut Vec<PyObject>) -> PyResult<()> {
+    if let Ok(sub_ob) = ob.getattr("sub_ob") {
+        children_with_base_name(sub_ob, name, out)?;
+    }
+    let children: HashMap<String, PyObject> = ob.getattr("children")?.extract()?;
+    for (key, child) in children {
+        if crate::object::alt_base_name(&key) == name {
+            out.push(child);
+        }
+    }
+    Ok(())
+}
+
+/// The kind string of a tree object, matching the `kind` its `to_dict`
+/// reports: `module`, `class`, `function`, `variable` or `alt`.
+fn py_kind(ob: &PyAny) -> &'static str {
+    if ob.is_instance_of::<Module>().unwrap_or(false) {
+        "module"
+    } else if ob.is_instance_of::<Class>().unwrap_or(false) {
+        "class"
+    } else if ob.is_instance_of::<Function>().unwrap_or(false) {
+        "function"
+    } else if ob.is_instance_of::<Variable>().unwrap_or(false) {
+        "variable"
+    } else if ob.is_instance_of::<AltObject>().unwrap_or(false) {
+        "alt"
+    } else {
+        "object"
+    }
+}
+
+/// Builds the dict form shared by all object kinds: name, path, span,
+/// kind and (recursively) children. Children are converted through their
+/// own `to_dict`.
+fn object_dict<'py>(py: Python<'py>, ob: &Object, kind: &str) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &ob.name)?;
+    dict.set_item("path", ob.object_path.__str__())?;
+    let span = PyDict::new(py);
+    span.set_item("filename", &ob.source_span.filename)?;
+    span.set_item("start_line", ob.source_span.start_line)?;
+    span.set_item("end_line", ob.source_span.end_line)?;
+    dict.set_item("span", span)?;
+    dict.set_item("kind", kind)?;
+    let children = PyDict::new(py);
+    for (name, child) in &ob.children {
+        children.set_item(name, child.as_ref(py).call_method0("to_dict")?)?;
+    }
+    dict.set_item("children", children)?;
+    Ok(dict)
 }
 
 impl PartialEq for Object {
@@ -161,38 +367,96 @@ pub struct AltObject {
 #[pymethods]
 impl AltObject {
     #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, sub_ob, children, module_path = "".to_string(),
+        type_checking_only = false, qualname = "".to_string()
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         source_span: SourceSpan,
         name: String,
         object_path: ObjectPath,
         sub_ob: PyObject,
         children: HashMap<String, PyObject>,
+        module_path: String,
+        type_checking_only: bool,
+        qualname: String,
     ) -> (Self, Object) {
-        let ob = Object::new(source_span, name.clone(), object_path, children);
+        let ob = Object::new(
+            source_span,
+            name.clone(),
+            object_path,
+            children,
+            module_path,
+            type_checking_only,
+            qualname,
+        );
         let alt = AltObject {
             alt_name: name,
             sub_ob,
         };
         (alt, ob)
     }
+
+    /// A plain-dict form of this alt-object, suitable for `json.dumps`.
+    /// The wrapped definition appears under `"sub_ob"`.
+    fn to_dict(self_: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = self_.py();
+        let dict = object_dict(py, self_.as_ref(), "alt")?;
+        dict.set_item("sub_ob", self_.sub_ob.as_ref(py).call_method0("to_dict")?)?;
+        Ok(dict.into())
+    }
 }
 
 #[pyclass(extends=Object)]
 #[derive(Clone, Debug)]
-pub struct Module;
+pub struct Module {
+    /// How completely this module was parsed: `"ok"`, `"partial"` (some
+    /// of its files or subpackages were skipped during a lenient scan)
+    /// or `"skipped"` (the source failed to parse and this is an empty
+    /// placeholder).
+    #[pyo3(get, set)]
+    parse_status: String,
+    /// The names a literal module-level `__all__` declares, or `None`
+    /// when the module has no literal `__all__` assignment.
+    #[pyo3(get, set)]
+    dunder_all: Option<Vec<String>>,
+}
 
 #[pymethods]
 impl Module {
     #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, children, module_path = "".to_string(),
+        type_checking_only = false, parse_status = "ok".to_string(),
+        qualname = "".to_string(), dunder_all = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         source_span: SourceSpan,
         name: String,
         object_path: ObjectPath,
         children: HashMap<String, PyObject>,
+        module_path: String,
+        type_checking_only: bool,
+        parse_status: String,
+        qualname: String,
+        dunder_all: Option<Vec<String>>,
     ) -> (Self, Object) {
         (
-            Self {},
-            Object::new(source_span, name, object_path, children),
+            Self {
+                parse_status,
+                dunder_all,
+            },
+            Object::new(
+                source_span,
+                name,
+                object_path,
+                children,
+                module_path,
+                type_checking_only,
+                qualname,
+            ),
         )
     }
 
@@ -200,27 +464,300 @@ impl Module {
         "module".into()
     }
 
-    fn __repr__(&self) -> String {
-        self.__str__()
+    fn __repr__(self_: PyRef<'_, Self>) -> String {
+        let super_ = self_.as_ref();
+        format!(
+            "module {} @ {}",
+            super_.object_path.__str__(),
+            super_.source_span.__str__()
+        )
+    }
+
+    /// A plain-dict form of this module, suitable for `json.dumps`.
+    fn to_dict(self_: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = self_.py();
+        Ok(object_dict(py, self_.as_ref(), "module")?.into())
+    }
+
+    /// Walks the tree calling `pred` on every object (this module
+    /// included) and returns those for which it was truthy.
+    /// Exceptions raised by `pred` propagate to the caller.
+    fn filter_objects(self_: PyRef<'_, Self>, pred: &PyAny) -> PyResult<Vec<PyObject>> {
+        let py = pred.py();
+        let ob = self_.into_py(py);
+        let mut matches = Vec::new();
+        filter_objects_rec(ob.as_ref(py), pred, &mut matches, py)?;
+        Ok(matches)
+    }
+
+    /// Every module below this one, recursively: classes and functions
+    /// are skipped, nested packages included. This module itself is not
+    /// part of the list.
+    fn all_submodules(self_: &PyCell<Self>) -> PyResult<Vec<PyObject>> {
+        fn collect(py: Python<'_>, ob: &PyAny, out: &mut Vec<PyObject>) -> PyResult<()> {
+            let children: HashMap<String, PyObject> = ob.getattr("children")?.extract()?;
+            for child in children.values() {
+                let target = child.as_ref(py);
+                let target = target.getattr("sub_ob").unwrap_or(target);
+                if target.is_instance_of::<Module>()? {
+                    out.push(target.into_py(py));
+                    collect(py, target, out)?;
+                }
+            }
+            Ok(())
+        }
+
+        let py = self_.py();
+        let mut out = Vec::new();
+        collect(py, self_, &mut out)?;
+        Ok(out)
+    }
+
+    /// The deepest object in this module whose span fully contains the
+    /// line range `start..=end`; the module itself if no child does.
+    /// Useful for "show enclosing symbol" given a selection range.
+    fn enclosing_object(self_: &PyCell<Self>, start: i32, end: i32) -> PyResult<PyObject> {
+        fn descend(py: Python<'_>, ob: PyObject, start: i32, end: i32) -> PyResult<PyObject> {
+            let children: HashMap<String, PyObject> =
+                ob.as_ref(py).getattr("children")?.extract()?;
+            for child in children.values() {
+                let span: SourceSpan = child.as_ref(py).getattr("source_span")?.extract()?;
+                if span.start_line <= start && end <= span.end_line {
+                    return descend(py, child.clone(), start, end);
+                }
+            }
+            Ok(ob)
+        }
+
+        let py = self_.py();
+        descend(py, self_.into_py(py), start, end)
+    }
+
+    /// Every definition at the dotted `path` below this module: the
+    /// primary object plus any alternates (`foo#1`) sharing the base
+    /// name, at every level. `path` does not include the module's own
+    /// name.
+    fn find_all_by_path(self_: &PyCell<Self>, path: String) -> PyResult<Vec<PyObject>> {
+        let py = self_.py();
+        let mut current: Vec<PyObject> = vec![self_.into_py(py)];
+        for comp in path.split('.') {
+            let mut next = Vec::new();
+            for ob in current {
+                children_with_base_name(ob.as_ref(py), comp, &mut next)?;
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// The sorted top-level names of this module, with alternate
+    /// definitions (`foo#1`) folded into their base name.
+    fn top_level_names(self_: PyRef<'_, Self>) -> Vec<String> {
+        let super_ = self_.as_ref();
+        let mut names: Vec<String> = super_
+            .children
+            .keys()
+            .map(|name| crate::object::alt_base_name(name).to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// The lint-suppression directives in this module's file, as
+    /// `(line, text)` pairs: the comments whose text contains one of
+    /// `directives`. Goes through the lexer, so a `#` inside a string
+    /// never counts. Reads the source file.
+    #[pyo3(signature = (
+        directives = vec![
+            "noqa".to_string(), "type: ignore".to_string(), "pragma: no cover".to_string()
+        ]
+    ))]
+    fn suppression_comments(
+        self_: PyRef<'_, Self>,
+        directives: Vec<String>,
+    ) -> PyResult<Vec<(usize, String)>> {
+        let super_ = self_.as_ref();
+        let comments =
+            crate::object::comments_in_file(std::path::Path::new(&super_.source_span.filename))?;
+        Ok(comments
+            .into_iter()
+            .filter(|(_, text)| directives.iter().any(|d| text.contains(d)))
+            .collect())
+    }
+
+    /// The children of this module whose span points into the file
+    /// `path`. In a namespace package (or a merged tree) one logical
+    /// module can aggregate definitions from several physical files;
+    /// this narrows them to a single one.
+    fn children_in_file(self_: PyRef<'_, Self>, path: String) -> PyResult<Vec<PyObject>> {
+        let py = self_.py();
+        let super_ = self_.as_ref();
+        let mut out = Vec::new();
+        for child in super_.children.values() {
+            let span: SourceSpan = child.as_ref(py).getattr("source_span")?.extract()?;
+            if span.filename == path {
+                out.push(child.clone());
+            }
+        }
+        Ok(out)
+    }
+
+    /// The direct children making up this module's public API, sorted
+    /// by name: the names `__all__` lists when the module declares a
+    /// literal one, otherwise every child whose name has no leading
+    /// underscore. Alternates count under their base name.
+    fn public_api(self_: PyRef<'_, Self>) -> Vec<PyObject> {
+        let super_ = self_.as_ref();
+        let mut names: Vec<&String> = super_
+            .children
+            .keys()
+            .filter(|key| {
+                let name = crate::object::alt_base_name(key);
+                match &self_.dunder_all {
+                    Some(all) => all.iter().any(|n| n == name),
+                    None => !name.starts_with('_'),
+                }
+            })
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|key| super_.children[key].clone())
+            .collect()
+    }
+
+    /// The number of public direct children; the length of
+    /// `public_api`.
+    fn public_api_count(self_: PyRef<'_, Self>) -> usize {
+        Self::public_api(self_).len()
+    }
+
+    /// Resolves an import written in this module to the project-local
+    /// module or object defining it, as the target's `ObjectPath`.
+    /// `name` is the dotted text of the import target — the `from`
+    /// module plus the imported name, or the module of a plain
+    /// `import` — and `level` its leading dots (zero for an absolute
+    /// import). Only this module's own subtree is searchable, so the
+    /// project root sees every import; a relative level that climbs
+    /// above this module, or an import from outside the project,
+    /// resolves to `None`.
+    #[pyo3(signature = (name, level = 0))]
+    fn resolve_import(
+        self_: &PyCell<Self>,
+        name: String,
+        level: usize,
+    ) -> PyResult<Option<PyObject>> {
+        let py = self_.py();
+        let own_path = self_.borrow().as_ref().object_path.__str__();
+        let mut index: HashMap<String, PyObject> = HashMap::new();
+        index.insert(own_path.clone(), self_.into_py(py));
+        for sub in Self::all_submodules(self_)? {
+            let path = sub
+                .as_ref(py)
+                .getattr("object_path")?
+                .str()?
+                .to_str()?
+                .to_string();
+            index.insert(path, sub);
+        }
+        let path_of = |ob: &PyObject| -> PyResult<PyObject> {
+            Ok(ob.as_ref(py).getattr("object_path")?.into_py(py))
+        };
+        // A dotted path names either a module or a top-level object of
+        // one: `pkg.mod` is tried as the module `pkg.mod` before the
+        // definition `mod` inside `pkg`.
+        let resolve = |dotted: &str| -> PyResult<Option<PyObject>> {
+            if let Some(module) = index.get(dotted) {
+                return path_of(module).map(Some);
+            }
+            let Some((prefix, last)) = dotted.rsplit_once('.') else {
+                return Ok(None);
+            };
+            let Some(module) = index.get(prefix) else {
+                return Ok(None);
+            };
+            let children: HashMap<String, PyObject> =
+                module.as_ref(py).getattr("children")?.extract()?;
+            let mut names: Vec<&String> = children
+                .keys()
+                .filter(|key| crate::object::alt_base_name(key) == last)
+                .collect();
+            names.sort();
+            match names.first() {
+                Some(key) => path_of(&children[*key]).map(Some),
+                None => Ok(None),
+            }
+        };
+        if level == 0 {
+            // Absolute imports may or may not spell out this module's
+            // own dotted path.
+            if let Some(path) = resolve(&name)? {
+                return Ok(Some(path));
+            }
+            return resolve(&format!("{own_path}.{name}"));
+        }
+        let filename = self_.borrow().as_ref().source_span.filename.clone();
+        let is_package = std::path::Path::new(&filename)
+            .file_name()
+            .is_some_and(|file| file == "__init__.py");
+        let comps: Vec<&str> = own_path.split('.').collect();
+        let in_package = comps.len() + usize::from(is_package);
+        let Some(keep) = in_package.checked_sub(level) else {
+            return Ok(None);
+        };
+        let mut base = comps[..keep].join(".");
+        if !name.is_empty() {
+            base = format!("{}.{}", base, name);
+        }
+        resolve(&base)
     }
 }
 
 #[pyclass(extends=Object)]
 #[derive(Clone, Debug)]
-pub struct Class;
+pub struct Class {
+    /// The Rust-side class this was translated from, set by
+    /// [`class_to_py`]. Absent when constructed from Python.
+    native: Option<super::Class>,
+}
+
+impl Class {
+    fn native(&self) -> PyResult<&super::Class> {
+        self.native
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("Class was not created by the parser"))
+    }
+}
 
 #[pymethods]
 impl Class {
     #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, children, module_path = "".to_string(),
+        type_checking_only = false, qualname = "".to_string()
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         source_span: SourceSpan,
         name: String,
         object_path: ObjectPath,
         children: HashMap<String, PyObject>,
+        module_path: String,
+        type_checking_only: bool,
+        qualname: String,
     ) -> (Self, Object) {
         (
-            Self {},
-            Object::new(source_span, name, object_path, children),
+            Self { native: None },
+            Object::new(
+                source_span,
+                name,
+                object_path,
+                children,
+                module_path,
+                type_checking_only,
+                qualname,
+            ),
         )
     }
 
@@ -228,9 +765,157 @@ impl Class {
         "class".into()
     }
 
+    fn __repr__(self_: PyRef<'_, Self>) -> String {
+        let super_ = self_.as_ref();
+        format!(
+            "class {} @ {}",
+            super_.object_path.__str__(),
+            super_.source_span.__str__()
+        )
+    }
+
+    /// The rendered base-class references of this class in source
+    /// order, e.g. `["models.Model"]`. Keyword arguments like
+    /// `metaclass=` are not included.
+    fn bases(&self) -> PyResult<Vec<String>> {
+        Ok(self.native()?.base_names())
+    }
+
+    /// Whether one of this class's decorators refers to `name`: its
+    /// dotted callee (arguments stripped) either equals `name` or ends
+    /// in `.name`, so `@attr.s(frozen=True)` matches both `"attr.s"`
+    /// and `"s"`.
+    fn has_decorator(&self, name: &str) -> PyResult<bool> {
+        Ok(self.native()?.has_decorator(name))
+    }
+
+    /// The number of decorators on this class.
+    fn decorator_count(&self) -> PyResult<usize> {
+        Ok(self.native()?.decorator_count())
+    }
+
+    /// The decorators on this class as translated `ast` expression
+    /// nodes, in source order: an `ast.Name` for `@final`, an
+    /// `ast.Attribute` for a dotted decorator, an `ast.Call` —
+    /// keyword arguments included — for one with arguments.
+    #[getter]
+    fn decorators(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let ast = get_ast_symbol_table(py)?;
+        self.native()?
+            .decorators
+            .iter()
+            .map(|dec| expr_kind_to_py(dec.node.clone(), py, &ast).map(|d| d.into_py(py)))
+            .collect()
+    }
+
+    /// The instance attributes this class's methods assign on `self`,
+    /// sorted and deduped: the union of the `self.x = ...` targets
+    /// (primarily from `__init__`) across every method, looking
+    /// through alternate definitions.
+    fn instance_attributes(&self) -> PyResult<Vec<String>> {
+        Ok(self.native()?.instance_attributes())
+    }
+
+    /// Whether this class is a dataclass-style record: decorated with
+    /// `@dataclass` (possibly with arguments) or an attrs equivalent.
+    fn is_dataclass(&self) -> PyResult<bool> {
+        Ok(self.native()?.is_dataclass())
+    }
+
+    /// The annotated class-level attributes of this class as
+    /// `(name, annotation, default)` tuples in source order. For a
+    /// dataclass these are its fields.
+    fn fields(&self) -> PyResult<Vec<(String, String, Option<String>)>> {
+        Ok(self
+            .native()?
+            .fields()
+            .into_iter()
+            .map(|var| {
+                (
+                    var.data.name().to_string(),
+                    var.annotation().to_string(),
+                    var.value().map(str::to_string),
+                )
+            })
+            .collect())
+    }
+
+    /// A plain-dict form of this class, suitable for `json.dumps`.
+    fn to_dict(self_: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = self_.py();
+        Ok(object_dict(py, self_.as_ref(), "class")?.into())
+    }
+}
+
+#[pyclass(extends=Object, get_all, set_all)]
+#[derive(Clone, Debug)]
+pub struct Variable {
+    annotation: String,
+    value: Option<String>,
+    simple: bool,
+}
+
+#[pymethods]
+impl Variable {
+    #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, children, annotation, value, simple,
+        module_path = "".to_string(), type_checking_only = false,
+        qualname = "".to_string()
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        source_span: SourceSpan,
+        name: String,
+        object_path: ObjectPath,
+        children: HashMap<String, PyObject>,
+        annotation: String,
+        value: Option<String>,
+        simple: bool,
+        module_path: String,
+        type_checking_only: bool,
+        qualname: String,
+    ) -> (Self, Object) {
+        let var = Variable {
+            annotation,
+            value,
+            simple,
+        };
+        let object = Object::new(
+            source_span,
+            name,
+            object_path,
+            children,
+            module_path,
+            type_checking_only,
+            qualname,
+        );
+        (var, object)
+    }
+
+    fn __str__(&self) -> String {
+        "variable".into()
+    }
+
     fn __repr__(&self) -> String {
         self.__str__()
     }
+
+    /// Whether the declaration assigns a value (`x: int = 0`), as
+    /// opposed to a bare annotation (`x: int`).
+    fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// A plain-dict form of this variable, suitable for `json.dumps`.
+    fn to_dict(self_: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = self_.py();
+        let dict = object_dict(py, self_.as_ref(), "variable")?;
+        dict.set_item("annotation", &self_.annotation)?;
+        dict.set_item("value", &self_.value)?;
+        dict.set_item("simple", self_.simple)?;
+        Ok(dict.into())
+    }
 }
 
 #[pyclass]
@@ -268,14 +953,60 @@ pub struct Function {
     kwarg: Option<String>,
     formatted_args: String,
 
-    #[pyo3(get, set)]
     stmts: HashMap<i32, PyObject>,
+
+    /// Whether `stmts` already holds the translated `ast` form. False
+    /// only for a function built with `lazy_stmts`, until the first
+    /// access triggers the translation.
+    stmts_translated: bool,
+
+    /// Whether `stmts` was left empty because the function's body
+    /// exceeded the `max_body_lines` cap during parsing.
+    #[pyo3(get, set)]
+    body_truncated: bool,
+
+    /// The Rust-side function this was translated from, set by
+    /// [`function_to_py`]. Absent when constructed from Python.
+    native: Option<super::Function>,
+}
+
+impl Function {
+    fn native(&self) -> PyResult<&super::Function> {
+        self.native
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("Function was not created by the parser"))
+    }
+
+    /// The statement map in its translated form, converting it from the
+    /// Rust-side statements and caching the result on first access
+    /// when the function was built with `lazy_stmts`.
+    fn translated_stmts(&mut self, py: Python<'_>) -> PyResult<&HashMap<i32, PyObject>> {
+        if !self.stmts_translated {
+            if let Some(native) = &self.native {
+                let ast = get_ast_symbol_table(py)?;
+                self.stmts = native
+                    .stmts
+                    .iter()
+                    .map(|(k, v)| {
+                        stmt_kind_to_py(v.clone(), py, &ast).map(|v| (*k as i32, v.into_py(py)))
+                    })
+                    .try_collect()?;
+            }
+            self.stmts_translated = true;
+        }
+        Ok(&self.stmts)
+    }
 }
 
 #[pymethods]
 impl Function {
     #[allow(clippy::too_many_arguments)]
     #[new]
+    #[pyo3(signature = (
+        source_span, name, object_path, children, formal_params, formatted_args, stmts,
+        kwarg, module_path = "".to_string(), type_checking_only = false,
+        qualname = "".to_string(), body_truncated = false
+    ))]
     fn new(
         source_span: SourceSpan,
         name: String,
@@ -285,17 +1016,392 @@ impl Function {
         formatted_args: String,
         stmts: HashMap<i32, PyObject>,
         kwarg: Option<String>,
+        module_path: String,
+        type_checking_only: bool,
+        qualname: String,
+        body_truncated: bool,
     ) -> (Self, Object) {
         let func = Function {
             formal_params,
             kwarg,
             formatted_args,
             stmts,
+            stmts_translated: true,
+            body_truncated,
+            native: None,
         };
-        let object = Object::new(source_span, name, object_path, children);
+        let object = Object::new(
+            source_span,
+            name,
+            object_path,
+            children,
+            module_path,
+            type_checking_only,
+            qualname,
+        );
         (func, object)
     }
 
+    /// Lines of code of this function: the non-blank, non-comment lines
+    /// within its span, excluding its docstring. Each physical line of a
+    /// multi-line statement counts separately. Reads the source file.
+    fn sloc(&self) -> PyResult<usize> {
+        Ok(self.native()?.sloc()?)
+    }
+
+    /// Whether this function calls itself by name anywhere in its body.
+    /// Only direct self-calls are detected; mutual recursion is not.
+    fn is_recursive(&self) -> PyResult<bool> {
+        Ok(self.native()?.is_recursive())
+    }
+
+    /// Whether this function looks like a test by convention: its name
+    /// starts with `prefix`, it carries a decorator starting with one
+    /// of `markers`, or it is a `test...` method on a `TestCase`
+    /// subclass.
+    #[pyo3(signature = (
+        prefix = "test_".to_string(), markers = vec!["pytest.mark".to_string()]
+    ))]
+    fn is_test(&self, prefix: String, markers: Vec<String>) -> PyResult<bool> {
+        let markers: Vec<&str> = markers.iter().map(String::as_str).collect();
+        Ok(self.native()?.is_test(&prefix, &markers))
+    }
+
+    /// Whether this function's body has no executable statements: only
+    /// `pass`, `...` and an optional docstring. Flags unimplemented
+    /// functions and protocol methods.
+    fn is_stub(&self) -> PyResult<bool> {
+        Ok(self.native()?.is_stub())
+    }
+
+    /// The verbatim source text of this function, read from the file
+    /// its span points into.
+    fn source(&self) -> PyResult<String> {
+        Ok(self.native()?.source()?)
+    }
+
+    /// A unified diff of this function's source against `other`'s,
+    /// headed by the two dotted paths.
+    fn body_diff(&self, other: PyRef<'_, Self>) -> PyResult<String> {
+        Ok(self.native()?.body_diff(other.native()?)?)
+    }
+
+    /// The number of distinct callees this function invokes.
+    fn fan_out(&self) -> PyResult<usize> {
+        Ok(self.native()?.fan_out())
+    }
+
+    /// The calls this function makes to one of `names`, as
+    /// `(line, name)` pairs sorted by line. The default set flags
+    /// print-debugging leftovers; override it to catch
+    /// project-specific loggers too.
+    #[pyo3(signature = (
+        names = vec![
+            "print".to_string(), "breakpoint".to_string(), "pdb.set_trace".to_string()
+        ]
+    ))]
+    fn debug_calls(&self, names: Vec<String>) -> PyResult<Vec<(usize, String)>> {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Ok(self.native()?.debug_calls(&names))
+    }
+
+    /// The assert statements of this function as `(line, test, message)`
+    /// tuples sorted by line, with the test expression and optional
+    /// message rendered back to source text.
+    fn asserts(&self) -> PyResult<Vec<(usize, String, Option<String>)>> {
+        Ok(self.native()?.asserts())
+    }
+
+    /// The number of `return` statements anywhere in this function's
+    /// body.
+    fn return_count(&self) -> PyResult<usize> {
+        Ok(self.native()?.return_count())
+    }
+
+    /// The number of explicit exit points in this function's body:
+    /// `return` and `raise` statements combined.
+    fn exit_points(&self) -> PyResult<usize> {
+        Ok(self.native()?.exit_points())
+    }
+
+    /// The names this function declares `global`, sorted and deduped.
+    fn global_names(&self) -> PyResult<Vec<String>> {
+        Ok(self.native()?.global_names())
+    }
+
+    /// The context managers this function enters, one
+    /// `(line, context_expr, target, is_async)` tuple per `with` item
+    /// sorted by line: the rendered expression, the rendered `as`
+    /// target when present, and whether the `with` was `async`.
+    fn context_managers(&self) -> PyResult<Vec<super::ContextManager>> {
+        Ok(self.native()?.context_managers())
+    }
+
+    /// The yield expressions anywhere in this function's body, as
+    /// `(line, text)` pairs sorted by line, rendered back to source
+    /// (`yield x`, `yield from xs`, or a bare `yield`).
+    fn yields(&self) -> PyResult<Vec<(usize, String)>> {
+        Ok(self.native()?.yields())
+    }
+
+    /// The attribute accesses on `self` anywhere in this function's
+    /// body, as `(line, attr, is_write)` triples sorted by line:
+    /// `is_write` is set when the attribute is a store or delete
+    /// target, as in `self.x = ...`, and clear for plain reads.
+    fn self_attributes(&self) -> PyResult<Vec<(usize, String, bool)>> {
+        Ok(self.native()?.self_attributes())
+    }
+
+    /// The names this function declares `nonlocal`, sorted and deduped.
+    fn nonlocal_names(&self) -> PyResult<Vec<String>> {
+        Ok(self.native()?.nonlocal_names())
+    }
+
+    /// The dotted path of the nearest enclosing class, for building
+    /// `ClassName.method` references; `None` for a free function.
+    fn enclosing_class_path(&self) -> PyResult<Option<String>> {
+        Ok(self
+            .native()?
+            .data
+            .enclosing_class()
+            .map(ToString::to_string))
+    }
+
+    /// The shape of this function's signature as
+    /// `(posonly, normal, kwonly, has_vararg, has_kwarg)`: the counts
+    /// of each parameter group without iterating `formal_params`.
+    fn arity(&self) -> PyResult<(usize, usize, usize, bool, bool)> {
+        Ok(self.native()?.arity())
+    }
+
+    /// A normalized key for this function's signature shape: arity,
+    /// parameter kinds, annotations and defaults, with the parameter
+    /// names left out. Signature-compatible functions share a key.
+    fn signature_key(&self) -> PyResult<String> {
+        Ok(self.native()?.signature_key())
+    }
+
+    /// This function's signature as an `inspect.Signature`. Since the
+    /// parser never evaluates anything, annotations and defaults are
+    /// the rendered source strings rather than live objects.
+    fn to_inspect_signature(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let native = self.native()?;
+        let inspect = py.import("inspect")?;
+        let parameter = inspect.getattr("Parameter")?;
+        let empty = parameter.getattr("empty")?;
+        let mut params = Vec::new();
+        for (name, kind, annotation, default) in native.parameters() {
+            let kind = parameter.getattr(match kind {
+                "posonly" => "POSITIONAL_ONLY",
+                "vararg" => "VAR_POSITIONAL",
+                "kwonly" => "KEYWORD_ONLY",
+                "kwarg" => "VAR_KEYWORD",
+                _ => "POSITIONAL_OR_KEYWORD",
+            })?;
+            let kwargs = PyDict::new(py);
+            kwargs.set_item(
+                "annotation",
+                annotation.map_or(empty.into(), |a| a.into_py(py)),
+            )?;
+            kwargs.set_item("default", default.map_or(empty.into(), |d| d.into_py(py)))?;
+            params.push(parameter.call((name, kind), Some(kwargs))?);
+        }
+        let kwargs = PyDict::new(py);
+        if let Some(returns) = &native.returns {
+            kwargs.set_item("return_annotation", super::try_render_expr(&returns.node))?;
+        }
+        Ok(inspect
+            .getattr("Signature")?
+            .call((params,), Some(kwargs))?
+            .into_py(py))
+    }
+
+    /// Whether one of this function's decorators refers to `name`:
+    /// its dotted callee (arguments stripped) either equals `name` or
+    /// ends in `.name`, so `@functools.wraps(f)` matches both
+    /// `"functools.wraps"` and `"wraps"`.
+    fn has_decorator(&self, name: &str) -> PyResult<bool> {
+        Ok(self.native()?.has_decorator(name))
+    }
+
+    /// The number of decorators on this function.
+    fn decorator_count(&self) -> PyResult<usize> {
+        Ok(self.native()?.decorator_count())
+    }
+
+    /// The decorators on this function as `(name, path)` pairs, where
+    /// `path` is the dotted path of the project object the decorator
+    /// resolves to, or `None` for decorators defined outside the
+    /// project.
+    fn resolved_decorators(&self) -> PyResult<Vec<(String, Option<String>)>> {
+        Ok(self
+            .native()?
+            .resolved_decorators()
+            .iter()
+            .map(|(name, path)| (name.clone(), path.as_ref().map(ToString::to_string)))
+            .collect())
+    }
+
+    /// The return-type annotation of this function (`-> int`) as a
+    /// translated `ast` expression node, or `None` when the signature
+    /// has no annotation.
+    fn get_return_annotation(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let ast = get_ast_symbol_table(py)?;
+        self.native()?
+            .returns
+            .as_ref()
+            .map(|ret| expr_kind_to_py(ret.node.clone(), py, &ast).map(|r| r.into_py(py)))
+            .transpose()
+    }
+
+    /// The decorators on this function as translated `ast` expression
+    /// nodes, in source order: an `ast.Name` for `@property`, an
+    /// `ast.Attribute` for `@app.route`, an `ast.Call` — keyword
+    /// arguments included — for `@app.route("/", methods=["GET"])`.
+    #[getter]
+    fn decorators(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let ast = get_ast_symbol_table(py)?;
+        self.native()?
+            .decorators
+            .iter()
+            .map(|dec| expr_kind_to_py(dec.node.clone(), py, &ast).map(|d| d.into_py(py)))
+            .collect()
+    }
+
+    /// The name of the `*args`-style vararg, if the function has one.
+    fn vararg_name(&self) -> PyResult<Option<String>> {
+        Ok(self.native()?.vararg_name())
+    }
+
+    /// The name of the `**kwargs`-style dict, if the function has one.
+    fn kwarg_name(&self) -> PyResult<Option<String>> {
+        Ok(self.native()?.kwarg_name())
+    }
+
+    /// Maps each keyword-only parameter that has a default onto the
+    /// rendered source of that default.
+    fn kwonly_defaults(&self) -> PyResult<HashMap<String, String>> {
+        Ok(self.native()?.kwonly_defaults())
+    }
+
+    /// Every string literal in this function's body as `(line, text)`
+    /// pairs, sorted by line. Includes the literal parts of f-strings;
+    /// byte strings are excluded.
+    fn string_literals(&self) -> PyResult<Vec<(usize, String)>> {
+        Ok(self.native()?.string_literals())
+    }
+
+    /// Every f-string in this function's body as
+    /// `(line, literal_parts, interpolations)` tuples, where the
+    /// interpolated expressions are rendered back to source text.
+    fn fstrings(&self) -> PyResult<Vec<super::FString>> {
+        Ok(self.native()?.fstrings())
+    }
+
+    /// Whether `other` is structurally the same function as this one:
+    /// same formal parameters and the same statements in the same order,
+    /// ignoring source positions.
+    fn structurally_equal(&self, other: &Function) -> PyResult<bool> {
+        Ok(self.native()?.structurally_equal(other.native()?))
+    }
+
+    /// Whether `other` is structurally the same function as this one
+    /// once each side's leading docstring is set aside; comments never
+    /// reach the statement map, so they never count either.
+    fn equal_ignoring_docs(&self, other: &Function) -> PyResult<bool> {
+        Ok(self.native()?.equal_ignoring_docs(other.native()?))
+    }
+
+    /// The flattened statements ordered by their line key: a shorthand
+    /// for `sorted(stmts.items())` that returns just the `ast` nodes.
+    /// Line order approximates source order; a compound statement
+    /// sorts before its contents.
+    fn statements_sorted(&mut self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let mut stmts: Vec<(i32, PyObject)> = self
+            .translated_stmts(py)?
+            .iter()
+            .map(|(line, stmt)| (*line, stmt.clone()))
+            .collect();
+        stmts.sort_by_key(|(line, _)| *line);
+        Ok(stmts.into_iter().map(|(_, stmt)| stmt).collect())
+    }
+
+    /// The flattened statement map keyed by line. For a tree built
+    /// with `lazy_stmts`, the `ast` translation happens here on first
+    /// access and is cached.
+    #[getter(stmts)]
+    fn get_stmts(&mut self, py: Python<'_>) -> PyResult<HashMap<i32, PyObject>> {
+        Ok(self.translated_stmts(py)?.clone())
+    }
+
+    #[setter(stmts)]
+    fn set_stmts(&mut self, stmts: HashMap<i32, PyObject>) {
+        self.stmts = stmts;
+        self.stmts_translated = true;
+    }
+
+    /// The last statement of the function's direct body as an `ast`
+    /// node, for "missing return" style lints. `None` when the body is
+    /// empty or was truncated.
+    fn last_statement(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let Some(stmt) = self.native()?.last_statement() else {
+            return Ok(None);
+        };
+        let ast = get_ast_symbol_table(py)?;
+        Ok(Some(
+            stmt_kind_to_py(stmt.node.clone(), py, &ast)?.into_py(py),
+        ))
+    }
+
+    /// The function's direct body statements in source order, as `ast`
+    /// nodes with their real block structure preserved, unlike the
+    /// flattened `stmts` dict. Nested `def` and `class` statements are
+    /// represented as child objects and so are skipped here, as in the
+    /// rest of the translation.
+    fn body_ast(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let ast = get_ast_symbol_table(py)?;
+        let mut nodes = Vec::new();
+        for stmt in &self.native()?.body {
+            if matches!(
+                stmt.node,
+                StmtKind::FunctionDef { .. }
+                    | StmtKind::AsyncFunctionDef { .. }
+                    | StmtKind::ClassDef { .. }
+            ) {
+                continue;
+            }
+            nodes.push(stmt_kind_to_py(stmt.node.clone(), py, &ast)?.into_py(py));
+        }
+        Ok(nodes)
+    }
+
+    /// A plain-dict form of this function, suitable for `json.dumps`.
+    /// Adds the formal params and the formatted signature to the common
+    /// object fields.
+    fn to_dict(self_: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = self_.py();
+        let dict = object_dict(py, self_.as_ref(), "function")?;
+        let params: Vec<&PyDict> = self_
+            .formal_params
+            .iter()
+            .map(|fp| {
+                let param = PyDict::new(py);
+                param.set_item("name", &fp.name)?;
+                param.set_item("has_default", fp.has_default)?;
+                let kind = match fp.kind {
+                    FormalParamKind::POSONLY => "posonly",
+                    FormalParamKind::NORMAL => "normal",
+                    FormalParamKind::KWONLY => "kwonly",
+                };
+                param.set_item("kind", kind)?;
+                PyResult::Ok(param)
+            })
+            .try_collect()?;
+        dict.set_item("params", params)?;
+        dict.set_item("signature", &self_.formatted_args)?;
+        Ok(dict.into())
+    }
+
     fn has_kwargs_dict(&self) -> bool {
         self.kwarg.is_some()
     }
@@ -311,7 +1417,8 @@ impl Function {
     }
 
     fn __repr__(self_: PyRef<'_, Self>) -> String {
-        Function::__str__(self_)
+        let span = self_.as_ref().source_span.__str__();
+        format!("{} @ {}", Function::__str__(self_), span)
     }
 
     fn __str__(self_: PyRef<'_, Self>) -> String {
@@ -721,7 +1828,7 @@ fn expr_kind_to_py<'a>(
             py_value!(ast, "JoinedStr", values)
         }
         ExprKind::Constant { value, kind } => {
-            let value = constant_to_py(value, py, ast)?;
+            let value = constant_to_py(value, py)?;
             py_value!(ast, "Constant", value, kind)
         }
         ExprKind::Attribute { value, attr, ctx } => {
@@ -776,34 +1883,34 @@ fn with_item_to_py<'a>(
     py_value!(ast, "withitem", context_expr, opt_var)
 }
 
-fn constant_to_py<'a>(
-    kind: Constant,
-    py: Python<'a>,
-    ast: &SymbolTable<'a>,
-) -> PyResult<&'a PyAny> {
-    let none = py.None();
-    let ellipsis = py.Ellipsis();
-
+/// Translates a literal into the plain Python value an `ast.Constant`
+/// (or `ast.MatchSingleton`) carries; the caller wraps it in the node.
+fn constant_to_py(kind: Constant, py: Python<'_>) -> PyResult<PyObject> {
     let value = match kind {
-        Constant::None => none,
+        Constant::None => py.None(),
         Constant::Bool(b) => b.into_py(py),
         Constant::Str(s) => s.into_py(py),
         Constant::Bytes(b) => b.into_py(py),
-        // FIXME: Handle BigInt properly
-        Constant::Int(_i) => 1.into_py(py),
+        // `num_bigint::BigInt` has no direct pyo3 conversion, but a
+        // Python `int` is arbitrary-precision too: going through the
+        // decimal string preserves values beyond `i64`.
+        Constant::Int(i) => py
+            .get_type::<PyLong>()
+            .call1((i.to_string(), 10))?
+            .into_py(py),
         Constant::Tuple(t) => PyTuple::new(
             py,
             t.into_iter()
-                .map(|c| constant_to_py(c, py, ast))
+                .map(|c| constant_to_py(c, py))
                 .try_collect::<_, Vec<_>, _>()?,
         )
         .into_py(py),
         Constant::Float(f) => f.into_py(py),
         Constant::Complex { real, imag } => PyComplex::from_doubles(py, real, imag).into_py(py),
-        Constant::Ellipsis => ellipsis,
+        Constant::Ellipsis => py.Ellipsis(),
     };
 
-    py_value!(ast, "Constant", value)
+    Ok(value)
 }
 
 fn match_pattern_to_py<'a>(
@@ -819,7 +1926,7 @@ fn match_pattern_to_py<'a>(
             py_value!(ast, "MatchValue", value)
         }
         PatternKind::MatchSingleton { value } => {
-            let value = constant_to_py(value, py, ast)?;
+            let value = constant_to_py(value, py)?;
             py_value!(ast, "MatchSingleton", value)
         }
         PatternKind::MatchSequence { patterns } => {
@@ -1083,6 +2190,46 @@ fn stmt_kind_to_py<'a>(
     val.map(|v| v.into())
 }
 
+/// Parses a single Python expression and returns the corresponding
+/// `ast` node. Raises `ValueError` when `code` is not an expression.
+#[pyfunction]
+pub fn parse_expr(py: Python<'_>, code: String) -> PyResult<&PyAny> {
+    let expr = rustpython_parser::parser::parse_expression(&code, "<string>")
+        .map_err(|e| PyValueError::new_err(format!("not a valid expression: {}", e)))?;
+    let ast = get_ast_symbol_table(py)?;
+    expr_kind_to_py(expr.node, py, &ast)
+}
+
+/// Parses a single Python statement and returns the corresponding
+/// `ast` node. Raises `ValueError` when `code` is not exactly one
+/// statement.
+#[pyfunction]
+pub fn parse_stmt(py: Python<'_>, code: String) -> PyResult<PyObject> {
+    let stmts = rustpython_parser::parser::parse_program(&code, "<string>")
+        .map_err(|e| PyValueError::new_err(format!("parse error: {}", e)))?;
+    let mut stmts = stmts.into_iter();
+    let (Some(stmt), None) = (stmts.next(), stmts.next()) else {
+        return Err(PyValueError::new_err("expected exactly one statement"));
+    };
+    let ast = get_ast_symbol_table(py)?;
+    stmt_kind_to_py(stmt.node, py, &ast)
+}
+
+/// Lexes the file at `path` into `(kind, text, start, end)` tuples,
+/// where `start` and `end` are `(row, column)` pairs (rows 1-based,
+/// columns 0-based). Complements the object tree for tools that need
+/// lexical detail, like highlighters.
+#[pyfunction]
+pub fn tokenize_file(path: String) -> PyResult<Vec<super::LexToken>> {
+    Ok(super::tokenize_file(std::path::Path::new(&path))?)
+}
+
+/// Lexes `code` like `tokenize_file`, without touching the filesystem.
+#[pyfunction]
+pub fn tokenize_source(code: String) -> Vec<super::LexToken> {
+    super::tokenize_source(&code)
+}
+
 fn source_span_to_py(py: Python, span: super::SourceSpan) -> PyResult<&PyAny> {
     let span_type = py.get_type::<SourceSpan>();
     span_type.call1((
@@ -1098,32 +2245,82 @@ fn object_path_to_py(py: Python, path: super::ObjectPath) -> PyResult<&PyAny> {
     path_type.call1((path.components, formatted_args))
 }
 
-pub fn module_to_py(py: Python, module: super::Module) -> PyResult<&PyAny> {
+pub fn module_to_py(py: Python, module: super::Module, lazy_stmts: bool) -> PyResult<&PyAny> {
     let mod_type = py.get_type::<Module>();
     let name = module.name().to_string();
+    let module_path = module.data.module_path.to_string();
+    let tco = module.data.type_checking_only;
+    let status = module.parse_status().as_str();
+    let qualname = module.data.qualname().to_string();
+    let dunder_all = module.dunder_all.clone();
     let ss = source_span_to_py(py, module.data.span)?;
     let path = object_path_to_py(py, module.data.obj_path)?;
     let children: HashMap<_, _> = module
         .data
         .children
         .into_iter()
-        .map(|(k, v)| object_to_py(py, v).map(|v| (k, v.into_py(py))))
+        .map(|(k, v)| object_to_py(py, v, lazy_stmts).map(|v| (k, v.into_py(py))))
         .try_collect()?;
-    mod_type.call1((ss, name, path, children))
+    mod_type.call1((
+        ss,
+        name,
+        path,
+        children,
+        module_path,
+        tco,
+        status,
+        qualname,
+        dunder_all,
+    ))
+}
+
+/// Translates `module` into a lightweight outline: nested plain dicts
+/// with only `name`, `kind`, `span` and `children`. Statements,
+/// arguments and the other heavy per-function state are never
+/// translated, so this stays cheap on large trees.
+pub fn module_outline_to_py<'py>(py: Python<'py>, module: &super::Module) -> PyResult<&'py PyDict> {
+    outline_dict(py, &module.data, super::ObjectKind::Module.as_str())
 }
 
-fn class_to_py(py: Python, class: super::Class) -> PyResult<&PyAny> {
+fn outline_dict<'py>(
+    py: Python<'py>,
+    data: &super::ObjectData,
+    kind: &str,
+) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", data.name())?;
+    dict.set_item("kind", kind)?;
+    let span = PyDict::new(py);
+    span.set_item("filename", data.span.path().to_str().unwrap())?;
+    span.set_item("start_line", data.span.start)?;
+    span.set_item("end_line", data.span.end)?;
+    dict.set_item("span", span)?;
+    let children = PyDict::new(py);
+    for (name, child) in &data.children {
+        children.set_item(name, outline_dict(py, child.data(), child.ob_type())?)?;
+    }
+    dict.set_item("children", children)?;
+    Ok(dict)
+}
+
+fn class_to_py(py: Python, class: super::Class, lazy_stmts: bool) -> PyResult<&PyAny> {
     let class_type = py.get_type::<Class>();
-    let name = class.data.name().to_string();
-    let ss = source_span_to_py(py, class.data.span)?;
-    let path = object_path_to_py(py, class.data.obj_path)?;
-    let children: HashMap<_, _> = class
-        .data
+    let data = class.data.clone();
+    let name = data.name().to_string();
+    let module_path = data.module_path.to_string();
+    let tco = data.type_checking_only;
+    let qualname = data.qualname().to_string();
+    let ss = source_span_to_py(py, data.span)?;
+    let path = object_path_to_py(py, data.obj_path)?;
+    let children: HashMap<_, _> = data
         .children
         .into_iter()
-        .map(|(k, v)| object_to_py(py, v).map(|v| (k, v.into_py(py))))
+        .map(|(k, v)| object_to_py(py, v, lazy_stmts).map(|v| (k, v.into_py(py))))
         .try_collect()?;
-    class_type.call1((ss, name, path, children))
+    let ob = class_type.call1((ss, name, path, children, module_path, tco, qualname))?;
+    let cell: &PyCell<Class> = ob.downcast()?;
+    cell.borrow_mut().native = Some(class);
+    Ok(ob)
 }
 
 fn formal_param_to_py(py: Python, fp: super::FormalParam) -> PyResult<&PyAny> {
@@ -1137,16 +2334,17 @@ fn formal_param_to_py(py: Python, fp: super::FormalParam) -> PyResult<&PyAny> {
     fp_type.call1((fp.name, fp.has_default, kind))
 }
 
-fn function_to_py(py: Python, func: super::Function) -> PyResult<&PyAny> {
+fn function_to_py(py: Python, func: super::Function, lazy_stmts: bool) -> PyResult<&PyAny> {
     let func_type = py.get_type::<Function>();
     let data = func.data.clone();
     let name = data.name().to_string();
+    let qualname = data.qualname().to_string();
     let ss = source_span_to_py(py, data.span)?;
     let path = object_path_to_py(py, data.obj_path)?;
     let children: HashMap<_, _> = data
         .children
         .into_iter()
-        .map(|(k, v)| object_to_py(py, v).map(|v| (k, v.into_py(py))))
+        .map(|(k, v)| object_to_py(py, v, lazy_stmts).map(|v| (k, v.into_py(py))))
         .try_collect()?;
     let formal_params: Vec<_> = func
         .formal_params()
@@ -1159,13 +2357,20 @@ fn function_to_py(py: Python, func: super::Function) -> PyResult<&PyAny> {
         None
     };
     let formatted_args = func.format_args();
-    let ast = get_ast_symbol_table(py)?;
-    let stmts: HashMap<_, _> = func
-        .stmts
-        .into_iter()
-        .map(|(k, v)| stmt_kind_to_py(v, py, &ast).map(|v| (k as i32, v.into_py(py))))
-        .try_collect()?;
-    func_type.call1((
+    // In lazy mode the statement map stays on the Rust side and
+    // `translated_stmts` builds the `ast` form on first access.
+    let stmts: HashMap<i32, PyObject> = if lazy_stmts {
+        HashMap::new()
+    } else {
+        let ast = get_ast_symbol_table(py)?;
+        func.stmts
+            .iter()
+            .map(|(k, v)| stmt_kind_to_py(v.clone(), py, &ast).map(|v| (*k as i32, v.into_py(py))))
+            .try_collect()?
+    };
+    let module_path = data.module_path.to_string();
+    let tco = data.type_checking_only;
+    let ob = func_type.call1((
         ss,
         name,
         path,
@@ -1174,30 +2379,74 @@ fn function_to_py(py: Python, func: super::Function) -> PyResult<&PyAny> {
         formatted_args,
         stmts,
         kwarg,
+        module_path,
+        tco,
+        qualname,
+        func.body_truncated(),
+    ))?;
+    let cell: &PyCell<Function> = ob.downcast()?;
+    let mut inner = cell.borrow_mut();
+    inner.native = Some(func);
+    inner.stmts_translated = !lazy_stmts;
+    drop(inner);
+    Ok(ob)
+}
+
+fn variable_to_py(py: Python, var: super::Variable, lazy_stmts: bool) -> PyResult<&PyAny> {
+    let var_type = py.get_type::<Variable>();
+    let name = var.data.name().to_string();
+    let module_path = var.data.module_path.to_string();
+    let tco = var.data.type_checking_only;
+    let qualname = var.data.qualname().to_string();
+    let ss = source_span_to_py(py, var.data.span)?;
+    let path = object_path_to_py(py, var.data.obj_path)?;
+    let children: HashMap<_, _> = var
+        .data
+        .children
+        .into_iter()
+        .map(|(k, v)| object_to_py(py, v, lazy_stmts).map(|v| (k, v.into_py(py))))
+        .try_collect()?;
+    let value = var.value;
+    let simple = var.simple;
+    var_type.call1((
+        ss,
+        name,
+        path,
+        children,
+        var.annotation,
+        value,
+        simple,
+        module_path,
+        tco,
+        qualname,
     ))
 }
 
-fn alt_object_to_py(py: Python, alt_ob: super::AltObject) -> PyResult<&PyAny> {
+fn alt_object_to_py(py: Python, alt_ob: super::AltObject, lazy_stmts: bool) -> PyResult<&PyAny> {
     let alt_object_type = py.get_type::<AltObject>();
     let name = alt_ob.data.name().to_string();
+    let module_path = alt_ob.data.module_path.to_string();
+    let tco = alt_ob.data.type_checking_only;
+    let qualname = alt_ob.data.qualname().to_string();
     let ss = source_span_to_py(py, alt_ob.data.span)?;
     let path = object_path_to_py(py, alt_ob.data.obj_path)?;
-    let sub_ob = object_to_py(py, *alt_ob.sub_ob)?;
+    let sub_ob = object_to_py(py, *alt_ob.sub_ob, lazy_stmts)?;
     let children: HashMap<_, _> = alt_ob
         .data
         .children
         .into_iter()
-        .map(|(k, v)| object_to_py(py, v).map(|v| (k, v.into_py(py))))
+        .map(|(k, v)| object_to_py(py, v, lazy_stmts).map(|v| (k, v.into_py(py))))
         .try_collect()?;
-    alt_object_type.call1((ss, name, path, sub_ob, children))
+    alt_object_type.call1((ss, name, path, sub_ob, children, module_path, tco, qualname))
 }
 
-fn object_to_py(py: Python, ob: super::Object) -> PyResult<&PyAny> {
+pub fn object_to_py(py: Python, ob: super::Object, lazy_stmts: bool) -> PyResult<&PyAny> {
     match ob {
-        super::Object::Module(module) => module_to_py(py, module),
-        super::Object::Class(class) => class_to_py(py, class),
-        super::Object::Function(func) => function_to_py(py, func),
-        super::Object::AltObject(alt_ob) => alt_object_to_py(py, alt_ob),
+        super::Object::Module(module) => module_to_py(py, module, lazy_stmts),
+        super::Object::Class(class) => class_to_py(py, class, lazy_stmts),
+        super::Object::Function(func) => function_to_py(py, func, lazy_stmts),
+        super::Object::Variable(var) => variable_to_py(py, var, lazy_stmts),
+        super::Object::AltObject(alt_ob) => alt_object_to_py(py, alt_ob, lazy_stmts),
     }
 }
 
@@ -1224,6 +2473,93 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_property_decorator_survives() {
+        pyo3::prepare_freethreaded_python();
+
+        let source = "@property\ndef f(self):\n    return 1\n";
+        let module = crate::project::Project::from_reader("file.py", source.as_bytes()).unwrap();
+        let func = module
+            .into_children()
+            .into_iter()
+            .find_map(|ob| match ob {
+                crate::object::Object::Function(func) => Some(func),
+                _ => None,
+            })
+            .expect("source defines no top-level function");
+
+        Python::with_gil(|py| {
+            let ob = function_to_py(py, func, false).unwrap();
+            let decorators: Vec<PyObject> = ob.getattr("decorators").unwrap().extract().unwrap();
+            let name = decorators[0].as_ref(py).getattr("id").unwrap();
+            assert_eq!(name.extract::<String>().unwrap(), "property");
+        });
+    }
+
+    #[test]
+    fn test_return_annotation_survives() {
+        pyo3::prepare_freethreaded_python();
+
+        let source = "def f() -> List[int]:\n    pass\n";
+        let module = crate::project::Project::from_reader("file.py", source.as_bytes()).unwrap();
+        let func = module
+            .into_children()
+            .into_iter()
+            .find_map(|ob| match ob {
+                crate::object::Object::Function(func) => Some(func),
+                _ => None,
+            })
+            .expect("source defines no top-level function");
+
+        Python::with_gil(|py| {
+            let ob = function_to_py(py, func, false).unwrap();
+            let annotation = ob.call_method0("get_return_annotation").unwrap();
+            let name = annotation.getattr("value").unwrap().getattr("id").unwrap();
+            assert_eq!(name.extract::<String>().unwrap(), "List");
+        });
+    }
+
+    #[test]
+    fn test_int_constant() {
+        pyo3::prepare_freethreaded_python();
+
+        let assign = parse_single_stmt("x = 42");
+
+        Python::with_gil(|py| {
+            let ast = get_ast_symbol_table(py).unwrap();
+            let stmt = stmt_kind_to_py(assign, py, &ast).unwrap();
+            let value = stmt
+                .as_ref(py)
+                .getattr("value")
+                .unwrap()
+                .getattr("value")
+                .unwrap();
+            assert_eq!(value.extract::<i64>().unwrap(), 42);
+        });
+    }
+
+    #[test]
+    fn test_big_int_constant() {
+        pyo3::prepare_freethreaded_python();
+
+        let assign = parse_single_stmt("x = 123456789012345678901234567890");
+
+        Python::with_gil(|py| {
+            let ast = get_ast_symbol_table(py).unwrap();
+            let stmt = stmt_kind_to_py(assign, py, &ast).unwrap();
+            let value = stmt
+                .as_ref(py)
+                .getattr("value")
+                .unwrap()
+                .getattr("value")
+                .unwrap();
+            let expected = py
+                .eval("123456789012345678901234567890", None, None)
+                .unwrap();
+            assert!(value.eq(expected).unwrap());
+        });
+    }
+
     #[test]
     fn test_stmt_kind_for() {
         pyo3::prepare_freethreaded_python();
@@ -1,48 +1,822 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
 
 use rayon::prelude::*;
 
-use crate::object::{Module, ModuleCreator, Object, ObjectPath};
+use crate::object::{
+    Function, ModNamer, Module, ModuleCreator, Object, ObjectKind, ObjectPath, ParseStatus,
+};
 
 pub mod py;
 
 pub struct Project {
     pub root: PathBuf,
     pub root_ob: Module,
+    /// The Python version the sources were assumed to target, from
+    /// [`ProjectOptions::python_version`].
+    pub python_version: Option<String>,
+    /// The errors skipped over during a lenient parse; empty otherwise.
+    pub errors: Vec<ProjectError>,
+}
+
+/// Options controlling how a [`Project`] is built.
+#[derive(Clone, Default)]
+pub struct ProjectOptions {
+    /// Report all [`crate::object::SourceSpan`] paths relative to the
+    /// project root, instead of the paths passed to [`Project::create`].
+    pub relative_paths: bool,
+
+    /// Stop recursing into subdirectories beyond this depth; the root is
+    /// at depth zero, and files at the cut-off level are still parsed.
+    /// `None` walks the whole tree.
+    pub max_depth: Option<usize>,
+
+    /// Keep going when a file or subdirectory fails to parse or read:
+    /// the failing unit is skipped and the error collected on
+    /// [`Project::errors`], instead of aborting the whole scan.
+    ///
+    /// This is also the escape hatch for syntax newer than the pinned
+    /// grammar: `rustpython_parser` 0.2 has no PEP 695 type parameters
+    /// (`def f[T](...)`, `class C[T]:`, `type Alias = ...`), so files
+    /// using them fail to parse and there are no `type_params` fields
+    /// to capture on the model until the parser grows them. Lenient
+    /// mode keeps the rest of the project usable past such files.
+    pub lenient: bool,
+
+    /// During a lenient scan, keep modules that failed to parse in the
+    /// tree as empty placeholders marked [`ParseStatus::Skipped`],
+    /// instead of dropping them. No effect unless `lenient` is set.
+    pub keep_skipped: bool,
+
+    /// Also parse extension-less files whose first line is a Python
+    /// shebang (`#!/usr/bin/env python` and the like), as found in
+    /// `bin/` directories of tools. `.pyw` files are always included.
+    pub include_scripts: bool,
+
+    /// Overrides how a module's name is derived from its file: the
+    /// callback receives the file path and returns the name. `None`
+    /// keeps the default rule, where `__init__.py` maps to the parent
+    /// directory and every other file drops its extension.
+    pub mod_namer: Option<ModNamer>,
+
+    /// Merge a package that is just a folder with an `__init__.py` —
+    /// no submodules, no subpackages — into its parent namespace,
+    /// instead of keeping a one-entry module in the tree. The merged
+    /// objects keep their spans and dotted paths, so they still point
+    /// at the original file. Packages that were skipped or partially
+    /// parsed are never collapsed.
+    pub collapse_trivial_packages: bool,
+
+    /// Skip statement extraction for functions whose span exceeds this
+    /// many lines: their signature and nested definitions are kept, but
+    /// `stmts` stays empty and [`Function::body_truncated`] is set.
+    /// Caps the memory spent on generated files with enormous bodies.
+    /// `None` extracts every body.
+    ///
+    /// [`Function::body_truncated`]: crate::object::Function::body_truncated
+    pub max_body_lines: Option<usize>,
+
+    /// The name of a source-root directory (`src` in the common
+    /// `src/mypkg/...` layout) to strip from the front of every dotted
+    /// path, so the tree yields import-correct paths like `mypkg.mod`
+    /// instead of `src.mypkg.mod`. The source-root module itself keeps
+    /// its name. `None` leaves paths untouched.
+    pub source_root: Option<String>,
+
+    /// The Python version the sources are assumed to target, e.g.
+    /// `"3.10"`. `rustpython_parser` implements a single fixed grammar,
+    /// so this cannot change how files parse; it is recorded on the
+    /// [`Project`] so that consumers can tell which version a tree was
+    /// parsed under and detect mismatches themselves.
+    pub python_version: Option<String>,
+}
+
+impl std::fmt::Debug for ProjectOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProjectOptions")
+            .field("relative_paths", &self.relative_paths)
+            .field("max_depth", &self.max_depth)
+            .field("lenient", &self.lenient)
+            .field("keep_skipped", &self.keep_skipped)
+            .field("include_scripts", &self.include_scripts)
+            .field("mod_namer", &self.mod_namer.as_ref().map(|_| "<callback>"))
+            .field("collapse_trivial_packages", &self.collapse_trivial_packages)
+            .field("max_body_lines", &self.max_body_lines)
+            .field("source_root", &self.source_root)
+            .field("python_version", &self.python_version)
+            .finish()
+    }
 }
 
 impl Project {
     pub fn create(root: PathBuf) -> Result<Self> {
-        let root_ob = module_from_dir(ObjectPath::default(), root.clone())?
-            .ok_or_else(|| ProjectError::EmptyRoot(root.clone()))?;
-        Ok(Self { root_ob, root })
+        Self::create_with_options(root, ProjectOptions::default())
+    }
+
+    pub fn create_with_options(root: PathBuf, options: ProjectOptions) -> Result<Self> {
+        let (root_ob, errors) = module_from_dir(
+            ObjectPath::default(),
+            root.clone(),
+            options.max_depth,
+            &options,
+        )?;
+        let mut root_ob = root_ob.ok_or_else(|| ProjectError::EmptyRoot(root.clone()))?;
+        if options.relative_paths {
+            root_ob.make_spans_relative(&root);
+        }
+        root_ob.resolve_decorators();
+        Ok(Self {
+            root_ob,
+            root,
+            python_version: options.python_version,
+            errors,
+        })
+    }
+
+    /// Counts the objects in this project by kind, in one walk:
+    /// `(modules, classes, functions, alt_objects)`. The definitions
+    /// wrapped by alt-objects count towards their own kind as well as
+    /// the alt-object total.
+    pub fn counts(&self) -> (usize, usize, usize, usize) {
+        fn count(ob: &Object, counts: &mut (usize, usize, usize, usize)) {
+            match ob {
+                Object::Module(_) => counts.0 += 1,
+                Object::Class(_) => counts.1 += 1,
+                Object::Function(_) => counts.2 += 1,
+                Object::Variable(_) => {}
+                Object::AltObject(_) => {
+                    counts.3 += 1;
+                    if let Some(sub_ob) = ob.sub_object() {
+                        count(sub_ob, counts);
+                    }
+                }
+            }
+            for child in ob.children() {
+                count(child, counts);
+            }
+        }
+
+        let mut counts = (1, 0, 0, 0);
+        for child in self.root_ob.children() {
+            count(child, &mut counts);
+        }
+        counts
+    }
+
+    /// One flat map from fully-qualified dotted path to object,
+    /// covering everything below the root module in a single walk.
+    /// Alternate definitions are keyed by their `name#N` paths, so
+    /// every definition is addressable. The root module itself is not
+    /// in the map, since it is not an [`Object`].
+    pub fn symbol_table(&self) -> HashMap<String, &Object> {
+        fn insert<'a>(ob: &'a Object, table: &mut HashMap<String, &'a Object>) {
+            table.insert(ob.data().obj_path().to_string(), ob);
+            if let Some(sub_ob) = ob.sub_object() {
+                insert(sub_ob, table);
+            }
+            for child in ob.children() {
+                insert(child, table);
+            }
+        }
+
+        let mut table = HashMap::new();
+        for child in self.root_ob.children() {
+            insert(child, &mut table);
+        }
+        table
+    }
+
+    /// The total number of flattened statements retained by the object
+    /// model: the summed sizes of every function's statement map. The
+    /// model does not keep statements outside function bodies, so
+    /// module-level code does not contribute.
+    pub fn total_statements(&self) -> usize {
+        self.root_ob
+            .all_functions()
+            .iter()
+            .map(|(_, func)| func.statement_count())
+            .sum()
+    }
+
+    /// Groups structurally identical functions across the whole project.
+    /// Candidates are bucketed by [`Function::structural_hash`] and then
+    /// confirmed with [`Function::structurally_equal`], so hash
+    /// collisions cannot produce false positives. Only groups with at
+    /// least two members are returned, sorted for determinism.
+    pub fn find_duplicate_functions(&self) -> Vec<Vec<ObjectPath>> {
+        let mut buckets: HashMap<u64, Vec<(&ObjectPath, &Function)>> = HashMap::new();
+        for (path, func) in self.root_ob.all_functions() {
+            buckets
+                .entry(func.structural_hash())
+                .or_default()
+                .push((path, func));
+        }
+
+        let mut groups = Vec::new();
+        for bucket in buckets.into_values() {
+            let mut confirmed: Vec<Vec<(&ObjectPath, &Function)>> = Vec::new();
+            for (path, func) in bucket {
+                match confirmed
+                    .iter_mut()
+                    .find(|group| func.structurally_equal(group[0].1))
+                {
+                    Some(group) => group.push((path, func)),
+                    None => confirmed.push(vec![(path, func)]),
+                }
+            }
+            for group in confirmed {
+                if group.len() > 1 {
+                    let mut paths: Vec<ObjectPath> =
+                        group.into_iter().map(|(path, _)| path.clone()).collect();
+                    paths.sort_by_key(|p| p.to_string());
+                    groups.push(paths);
+                }
+            }
+        }
+        groups.sort_by_key(|g| g[0].to_string());
+        groups
+    }
+
+    /// The span path of `obj` relative to this project's root, for
+    /// display. A path not under the root — including one already made
+    /// relative by [`ProjectOptions::relative_paths`] — comes back
+    /// unchanged.
+    pub fn relative_path_of(&self, obj: &Object) -> PathBuf {
+        let path = obj.data().span().path();
+        path.strip_prefix(&self.root).unwrap_or(path).to_path_buf()
+    }
+
+    /// The project-local import edges: one `(importer, imported)` pair
+    /// per resolved import, deduplicated and sorted for determinism.
+    /// Relative imports are resolved against the importing module's
+    /// package; `from pkg import name` prefers the submodule
+    /// `pkg.name` when one exists and falls back to `pkg` itself.
+    /// Imports of modules outside the project are left out, as are
+    /// function-local imports (see [`Module::imports`]).
+    pub fn import_graph(&self) -> Vec<(ObjectPath, ObjectPath)> {
+        let mut modules: Vec<&Module> = vec![&self.root_ob];
+        modules.extend(self.root_ob.all_submodules());
+        let index: HashMap<String, &ObjectPath> = modules
+            .iter()
+            .map(|m| (m.path().to_string(), m.path()))
+            .collect();
+        let root_name = self.root_ob.name();
+        // Absolute imports are written from the package's point of
+        // view, which may or may not include the root module's name.
+        let qualify = |dotted: &str| -> Option<&String> {
+            if let Some((key, _)) = index.get_key_value(dotted) {
+                return Some(key);
+            }
+            index
+                .get_key_value(&format!("{root_name}.{dotted}"))
+                .map(|(key, _)| key)
+        };
+
+        let mut edges = Vec::new();
+        for module in &modules {
+            for imp in module.imports() {
+                let mut push = |key: &String| {
+                    let target = index[key];
+                    if target.to_string() != module.path().to_string() {
+                        edges.push((module.path().clone(), target.clone()));
+                    }
+                };
+                if imp.level == 0 && imp.names.is_empty() {
+                    // `import a.b.c` couples to the deepest project
+                    // module along the dotted chain.
+                    let mut parts: Vec<&str> = imp.module.split('.').collect();
+                    while !parts.is_empty() {
+                        if let Some(key) = qualify(&parts.join(".")) {
+                            push(key);
+                            break;
+                        }
+                        parts.pop();
+                    }
+                    continue;
+                }
+                let base = if imp.level > 0 {
+                    let comps = module.path().components();
+                    let in_package = comps.len() + usize::from(module.is_package());
+                    let Some(keep) = in_package.checked_sub(imp.level) else {
+                        continue;
+                    };
+                    let mut base = comps[..keep].join(".");
+                    if !imp.module.is_empty() {
+                        base = format!("{}.{}", base, imp.module);
+                    }
+                    if !index.contains_key(&base) {
+                        continue;
+                    }
+                    base
+                } else {
+                    match qualify(&imp.module) {
+                        Some(key) => key.clone(),
+                        None => continue,
+                    }
+                };
+                for name in &imp.names {
+                    match index.get_key_value(&format!("{base}.{name}")) {
+                        Some((key, _)) => push(key),
+                        None => push(&base),
+                    }
+                }
+            }
+        }
+        edges.sort_by_key(|(from, to)| (from.to_string(), to.to_string()));
+        edges.dedup_by_key(|(from, to)| (from.to_string(), to.to_string()));
+        edges
+    }
+
+    /// The import cycles among project-local modules: every strongly
+    /// connected component of [`Project::import_graph`] with more than
+    /// one module, as its sorted module paths. An empty result means
+    /// the project's imports are acyclic at load time, which makes
+    /// this a cheap CI gate against cycles creeping in.
+    pub fn circular_imports(&self) -> Vec<Vec<ObjectPath>> {
+        let edges = self.import_graph();
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut nodes: Vec<ObjectPath> = Vec::new();
+        let mut adj: Vec<Vec<usize>> = Vec::new();
+        for (from, to) in &edges {
+            for path in [from, to] {
+                ids.entry(path.to_string()).or_insert_with(|| {
+                    nodes.push(path.clone());
+                    adj.push(Vec::new());
+                    nodes.len() - 1
+                });
+            }
+        }
+        for (from, to) in &edges {
+            adj[ids[&from.to_string()]].push(ids[&to.to_string()]);
+        }
+
+        // Tarjan's algorithm; every SCC of size > 1 is a cycle. The
+        // graph excludes self-edges, so singletons never qualify.
+        #[allow(clippy::too_many_arguments)]
+        fn connect(
+            v: usize,
+            adj: &[Vec<usize>],
+            index: &mut [Option<usize>],
+            low: &mut [usize],
+            stack: &mut Vec<usize>,
+            on_stack: &mut [bool],
+            counter: &mut usize,
+            sccs: &mut Vec<Vec<usize>>,
+        ) {
+            index[v] = Some(*counter);
+            low[v] = *counter;
+            *counter += 1;
+            stack.push(v);
+            on_stack[v] = true;
+            for &w in &adj[v] {
+                if index[w].is_none() {
+                    connect(w, adj, index, low, stack, on_stack, counter, sccs);
+                    low[v] = low[v].min(low[w]);
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w].unwrap());
+                }
+            }
+            if Some(low[v]) == index[v] {
+                let mut scc = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    scc.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                sccs.push(scc);
+            }
+        }
+
+        let mut index = vec![None; nodes.len()];
+        let mut low = vec![0; nodes.len()];
+        let mut on_stack = vec![false; nodes.len()];
+        let mut stack = Vec::new();
+        let mut counter = 0;
+        let mut sccs = Vec::new();
+        for v in 0..nodes.len() {
+            if index[v].is_none() {
+                connect(
+                    v,
+                    &adj,
+                    &mut index,
+                    &mut low,
+                    &mut stack,
+                    &mut on_stack,
+                    &mut counter,
+                    &mut sccs,
+                );
+            }
+        }
+
+        let mut cycles: Vec<Vec<ObjectPath>> = sccs
+            .into_iter()
+            .filter(|scc| scc.len() > 1)
+            .map(|scc| {
+                let mut paths: Vec<ObjectPath> =
+                    scc.into_iter().map(|v| nodes[v].clone()).collect();
+                paths.sort_by_key(|p| p.to_string());
+                paths
+            })
+            .collect();
+        cycles.sort_by_key(|cycle| cycle[0].to_string());
+        cycles
+    }
+
+    /// Every object in the tree whose span points into the file at
+    /// `path`: the inverse of the tree view, for building per-file
+    /// outlines or when one file feeds several logical modules.
+    /// Objects come back in tree order, sorted by span within each
+    /// level.
+    pub fn objects_in_file(&self, path: &Path) -> Vec<&Object> {
+        fn collect<'a>(ob: &'a Object, path: &Path, out: &mut Vec<&'a Object>) {
+            if ob.data().span().path() == path {
+                out.push(ob);
+            }
+            if let Some(sub_ob) = ob.sub_object() {
+                collect(sub_ob, path, out);
+            }
+            let mut children: Vec<&Object> = ob.children().collect();
+            children.sort_by_key(|child| child.data().span().start());
+            for child in children {
+                collect(child, path, out);
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut children: Vec<&Object> = self.root_ob.children().collect();
+        children.sort_by_key(|child| child.data().span().start());
+        for child in children {
+            collect(child, path, &mut out);
+        }
+        out
+    }
+
+    /// Renders the object tree as a GraphViz DOT digraph: one node per
+    /// object labeled with its name and kind, filled with a per-kind
+    /// color, and one edge per containment link. Pipe the result
+    /// through `dot -Tpng` for an instant picture of the structure.
+    pub fn to_dot(&self) -> String {
+        fn color(kind: ObjectKind) -> &'static str {
+            match kind {
+                ObjectKind::Module => "lightblue",
+                ObjectKind::Class => "khaki",
+                ObjectKind::Function => "palegreen",
+                ObjectKind::Variable => "lightgrey",
+                ObjectKind::Alt => "lightpink",
+            }
+        }
+
+        fn escape(name: &str) -> String {
+            name.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        fn node(out: &mut String, id: usize, name: &str, kind: ObjectKind) {
+            out.push_str(&format!(
+                "    n{} [label=\"{}\\n{}\", style=filled, fillcolor={}];\n",
+                id,
+                escape(name),
+                kind,
+                color(kind),
+            ));
+        }
+
+        fn walk(out: &mut String, next_id: &mut usize, parent: usize, ob: &Object) {
+            let id = *next_id;
+            *next_id += 1;
+            node(out, id, ob.data().name(), ob.kind());
+            out.push_str(&format!("    n{} -> n{};\n", parent, id));
+            if let Some(sub_ob) = ob.sub_object() {
+                walk(out, next_id, id, sub_ob);
+            }
+            for child in ob.children() {
+                walk(out, next_id, id, child);
+            }
+        }
+
+        let mut out = String::from("digraph project {\n    node [shape=box];\n");
+        node(&mut out, 0, self.root_ob.name(), ObjectKind::Module);
+        let mut next_id = 1;
+        for child in self.root_ob.children() {
+            walk(&mut out, &mut next_id, 0, child);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// The canonical paths of every function in the project that
+    /// [`Function::is_test`] recognizes under `prefix` and `markers`,
+    /// sorted for determinism. The usual call is
+    /// `test_functions("test_", &["pytest.mark"])`.
+    pub fn test_functions(&self, prefix: &str, markers: &[&str]) -> Vec<ObjectPath> {
+        let mut paths: Vec<ObjectPath> = self
+            .root_ob
+            .all_functions()
+            .into_iter()
+            .filter(|(_, func)| func.is_test(prefix, markers))
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.sort_by_key(|p| p.to_string());
+        paths
+    }
+
+    /// The classes in the project inheriting from `base_name`, as their
+    /// canonical paths sorted for determinism. A base reference matches
+    /// when its rendered text equals `base_name` or the two differ only
+    /// by a dotted prefix, so `"Model"` finds `class X(models.Model):`
+    /// and vice versa. Subclasses of matched project classes are
+    /// included too, so the transitive hierarchy below a framework base
+    /// comes back in one call; that resolution goes by name, not by
+    /// import analysis.
+    pub fn subclasses_of(&self, base_name: &str) -> Vec<ObjectPath> {
+        let mut classes = self.root_ob.all_classes();
+        let mut targets: Vec<String> = vec![base_name.to_string()];
+        let mut matched: Vec<ObjectPath> = Vec::new();
+        loop {
+            let mut progressed = false;
+            classes.retain(|(path, class)| {
+                let is_sub = class
+                    .base_names()
+                    .iter()
+                    .any(|base| targets.iter().any(|t| base_matches(base, t)));
+                if is_sub {
+                    targets.push(path.name().to_string());
+                    targets.push(path.to_string());
+                    matched.push((*path).clone());
+                    progressed = true;
+                }
+                !is_sub
+            });
+            if !progressed {
+                break;
+            }
+        }
+        matched.sort_by_key(|p| p.to_string());
+        matched
+    }
+
+    /// Lists the files a parse of `root` would include: the same
+    /// directory traversal as [`Project::create_with_options`],
+    /// honouring `max_depth` and `lenient`, but with no parsing at all.
+    /// Useful as a dry run to check which files a scan will pick up.
+    pub fn list_files(root: &Path, options: &ProjectOptions) -> Result<Vec<PathBuf>> {
+        fn walk(
+            dir: &Path,
+            max_depth: Option<usize>,
+            lenient: bool,
+            include_scripts: bool,
+            files: &mut Vec<PathBuf>,
+        ) -> Result<()> {
+            let drc = match DirChildren::create(dir, include_scripts) {
+                Ok(drc) => drc,
+                Err(_) if lenient => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            let Some(init) = drc.init else {
+                return Ok(());
+            };
+            files.push(init);
+            files.extend(drc.files);
+            if max_depth != Some(0) {
+                for sub in drc.dirs {
+                    walk(
+                        &sub,
+                        max_depth.map(|d| d - 1),
+                        lenient,
+                        include_scripts,
+                        files,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+
+        let mut files = Vec::new();
+        walk(
+            root,
+            options.max_depth,
+            options.lenient,
+            options.include_scripts,
+            &mut files,
+        )?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// The declared encoding of every file a default parse of `root`
+    /// would include, as `(path, encoding)` pairs sorted by path. A
+    /// UTF-8 BOM reports `"utf-8-sig"`; otherwise the PEP 263 coding
+    /// cookie on one of the first two lines is reported, defaulting to
+    /// `"utf-8"`. Only those lines are read and nothing is decoded or
+    /// parsed, so this stays cheap for a whole-codebase audit.
+    pub fn detect_encodings(root: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let files = Self::list_files(root, &ProjectOptions::default())?;
+        files
+            .into_iter()
+            .map(|file| declared_encoding(&file).map(|enc| (file, enc)))
+            .collect()
+    }
+
+    /// Builds a project from exactly the files listed in the manifest
+    /// at `manifest`: one path per line, relative to the manifest's
+    /// directory (absolute paths must still fall under it). Blank
+    /// lines and `#` comments are skipped. Parent packages whose
+    /// `__init__.py` is not listed become empty placeholder modules,
+    /// so the tree keeps its shape. Made for build systems that
+    /// already know the precise file set and want reproducible scans.
+    pub fn from_manifest(manifest: &Path) -> Result<Self> {
+        fn build_package(
+            dir: &Path,
+            par_path: &ObjectPath,
+            files: &[PathBuf],
+            options: &ProjectOptions,
+        ) -> Result<Module> {
+            let init = dir.join("__init__.py");
+            let mut main_mod = if files.contains(&init) {
+                mod_from_file(init.clone(), par_path.clone(), options)?
+            } else {
+                // A package pulled in only to hold listed files below
+                // it; an empty module stands in for its `__init__.py`.
+                ModuleCreator::new(init.clone(), 0, par_path.clone()).create(Vec::new())
+            };
+            let mut new_path = par_path.clone();
+            new_path.append_part(main_mod.name().to_string());
+
+            let mut subdirs: Vec<PathBuf> = Vec::new();
+            for file in files {
+                if *file == init {
+                    continue;
+                }
+                if file.parent() == Some(dir) {
+                    let module = mod_from_file(file.clone(), new_path.clone(), options)?;
+                    main_mod.append_child(Object::Module(module));
+                } else {
+                    let rel = file.strip_prefix(dir).expect("file grouped under dir");
+                    let sub = dir.join(rel.components().next().unwrap());
+                    if !subdirs.contains(&sub) {
+                        subdirs.push(sub);
+                    }
+                }
+            }
+            for sub in subdirs {
+                let group: Vec<PathBuf> = files
+                    .iter()
+                    .filter(|file| file.starts_with(&sub))
+                    .cloned()
+                    .collect();
+                let module = build_package(&sub, &new_path, &group, options)?;
+                main_mod.append_child(Object::Module(module));
+            }
+            Ok(main_mod)
+        }
+
+        let root = manifest
+            .parent()
+            .filter(|par| !par.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+        let text = std::fs::read_to_string(manifest)?;
+        let mut files = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let path = PathBuf::from(line);
+            let path = if path.is_absolute() {
+                path
+            } else {
+                root.join(path)
+            };
+            if !path.starts_with(&root) {
+                return Err(ProjectError::OutsideRoot(path));
+            }
+            files.push(path);
+        }
+        files.sort();
+        files.dedup();
+
+        let options = ProjectOptions::default();
+        let mut root_ob = build_package(&root, &ObjectPath::default(), &files, &options)?;
+        root_ob.resolve_decorators();
+        Ok(Self {
+            root,
+            root_ob,
+            python_version: None,
+            errors: Vec::new(),
+        })
+    }
+
+    /// Parses a single module out of `reader`, for code piped on stdin
+    /// or otherwise not backed by a real file. `name` becomes the
+    /// module name; the synthetic filename on every span is `<stdin>`.
+    pub fn from_reader(name: &str, mut reader: impl std::io::Read) -> Result<Module> {
+        let mut code = String::new();
+        reader.read_to_string(&mut code)?;
+        let line_cnt = code.bytes().filter(|c| c == &b'\n').count() + 1;
+        let stmts = rustpython_parser::parser::parse_program(&code, "<stdin>")?;
+        let name = name.to_string();
+        Ok(
+            ModuleCreator::new(PathBuf::from("<stdin>"), line_cnt, ObjectPath::default())
+                .with_mod_namer(std::sync::Arc::new(move |_| name.clone()))
+                .create(stmts),
+        )
+    }
+
+    /// Parses several sibling project roots in one call, returning one
+    /// module per root. Roots without a Python module are skipped; roots
+    /// with the same package name simply yield two modules, so no names
+    /// are ever lost to collisions.
+    pub fn create_multi(roots: Vec<PathBuf>) -> Result<Vec<Module>> {
+        let options = ProjectOptions::default();
+        let mut results = Vec::new();
+        roots
+            .into_par_iter()
+            .map(|root| module_from_dir(ObjectPath::default(), root, None, &options))
+            .collect_into_vec(&mut results);
+        let mut modules = Vec::new();
+        for result in results {
+            if let (Some(mut module), _) = result? {
+                module.resolve_decorators();
+                modules.push(module);
+            }
+        }
+        Ok(modules)
     }
 }
 
+#[cfg(feature = "serde")]
+impl Project {
+    /// Serializes the whole object tree to a JSON string, mirroring the
+    /// shape of the `to_dict` methods on the Python classes. Useful
+    /// when the consumer wants JSON anyway and translating the tree to
+    /// Python objects first would be wasted work.
+    pub fn to_json(&self) -> String {
+        self.root_ob.to_json_value().to_string()
+    }
+
+    // TODO: Implement binary save()/load() for caching parsed trees.
+    // Blocked on decoupling the model from the rustpython-ast types:
+    // `Arguments`, `Stmt` and `Expr` (held by Function and friends)
+    // implement no serde traits in rustpython-parser 0.2, so a
+    // bincode/postcard round-trip cannot be derived until the model
+    // owns serde-friendly mirrors of them.
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProjectError {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
-    #[error("OsString was not valid UTF-8")]
-    OsStringNotUtf8,
+    #[error("path {} is not valid UTF-8", .0.display())]
+    OsStringNotUtf8(PathBuf),
 
     #[error("parse error: {0}")]
     Parse(#[from] rustpython_parser::error::ParseError),
 
     #[error("no Python module in {}", .0.display())]
     EmptyRoot(PathBuf),
+
+    #[error("manifest entry {} is outside the manifest's directory", .0.display())]
+    OutsideRoot(PathBuf),
 }
 
 pub type Result<T> = std::result::Result<T, ProjectError>;
 
-fn module_from_dir(par_path: ObjectPath, dir: PathBuf) -> Result<Option<Module>> {
-    let drc = DirChildren::create(&dir)?;
+fn module_from_dir(
+    par_path: ObjectPath,
+    dir: PathBuf,
+    depth_left: Option<usize>,
+    options: &ProjectOptions,
+) -> Result<(Option<Module>, Vec<ProjectError>)> {
+    let lenient = options.lenient;
+    let keep_skipped = options.lenient && options.keep_skipped;
+    let mut errors = Vec::new();
+    let mut partial = false;
+    // In lenient mode an unreadable directory or an unparseable
+    // `__init__.py` skips the package instead of aborting the scan.
+    let drc = match DirChildren::create(&dir, options.include_scripts) {
+        Ok(drc) => drc,
+        Err(e) if lenient => return Ok((None, vec![e])),
+        Err(e) => return Err(e),
+    };
     let Some(init) = drc.init else {
-        return Ok(None);
+        return Ok((None, errors));
     };
 
-    let mut main_mod = mod_from_file(init, par_path.clone())?;
+    let mut main_mod = match mod_from_file(init.clone(), par_path.clone(), options) {
+        Ok(module) => module,
+        Err(e) if keep_skipped => {
+            return Ok((Some(skipped_module(init, par_path, options)), vec![e]))
+        }
+        Err(e) if lenient => return Ok((None, vec![e])),
+        Err(e) => return Err(e),
+    };
     let mut new_path = par_path;
     new_path.append_part(main_mod.name().to_string());
 
@@ -50,39 +824,178 @@ fn module_from_dir(par_path: ObjectPath, dir: PathBuf) -> Result<Option<Module>>
         let mut child_mods = Vec::new();
         drc.files
             .into_par_iter()
-            .map(|f| mod_from_file(f, new_path.clone()))
+            .map(|f| mod_from_file(f.clone(), new_path.clone(), options).map_err(|e| (e, f)))
             .collect_into_vec(&mut child_mods);
         for child in child_mods {
-            let child = child?;
-            main_mod.append_child(Object::Module(child));
+            match child {
+                Ok(child) => main_mod.append_child(Object::Module(child)),
+                Err((e, path)) if lenient => {
+                    errors.push(e);
+                    partial = true;
+                    if keep_skipped {
+                        main_mod.append_child(Object::Module(skipped_module(
+                            path,
+                            new_path.clone(),
+                            options,
+                        )));
+                    }
+                }
+                Err((e, _)) => return Err(e),
+            }
         }
     }
 
-    {
+    if depth_left != Some(0) {
         let mut child_mods = Vec::new();
         drc.dirs
             .into_par_iter()
-            .map(|p| module_from_dir(new_path.clone(), p))
+            .map(|p| {
+                (
+                    module_from_dir(
+                        new_path.clone(),
+                        p.clone(),
+                        depth_left.map(|d| d - 1),
+                        options,
+                    ),
+                    p,
+                )
+            })
             .collect_into_vec(&mut child_mods);
-        for child in child_mods {
-            let child = child?;
+        for (child, dir_path) in child_mods {
+            let (child, child_errors) = child?;
+            // A subpackage that produced errors but no module was
+            // skipped entirely; the init path names the placeholder.
+            if child.is_none() && !child_errors.is_empty() {
+                partial = true;
+                if keep_skipped {
+                    main_mod.append_child(Object::Module(skipped_module(
+                        dir_path.join("__init__.py"),
+                        new_path.clone(),
+                        options,
+                    )));
+                }
+            }
+            errors.extend(child_errors);
             if let Some(child) = child {
-                main_mod.append_child(Object::Module(child));
+                if options.collapse_trivial_packages
+                    && !child.has_submodules()
+                    && child.parse_status() == ParseStatus::Ok
+                {
+                    for ob in child.into_children() {
+                        main_mod.append_child(ob);
+                    }
+                } else {
+                    main_mod.append_child(Object::Module(child));
+                }
             }
         }
     }
 
-    Ok(Some(main_mod))
+    if partial {
+        main_mod.set_parse_status(ParseStatus::Partial);
+    }
+    Ok((Some(main_mod), errors))
+}
+
+/// An empty placeholder for a module whose source could not be parsed,
+/// so that skipped files still show up in a lenient tree.
+fn skipped_module(path: PathBuf, par_path: ObjectPath, options: &ProjectOptions) -> Module {
+    let mut creator = ModuleCreator::new(path, 0, par_path);
+    if let Some(namer) = &options.mod_namer {
+        creator = creator.with_mod_namer(namer.clone());
+    }
+    if let Some(root) = &options.source_root {
+        creator = creator.with_source_root(root.clone());
+    }
+    let mut module = creator.create(Vec::new());
+    module.set_parse_status(ParseStatus::Skipped);
+    module
 }
 
-fn mod_from_file(path: PathBuf, par_path: ObjectPath) -> Result<Module> {
+fn mod_from_file(path: PathBuf, par_path: ObjectPath, options: &ProjectOptions) -> Result<Module> {
     let code = std::fs::read_to_string(&path)?;
     let line_cnt = code.bytes().filter(|c| c == &b'\n').count() + 1;
     let stmts = rustpython_parser::parser::parse_program(
         &code,
-        path.to_str().ok_or(ProjectError::OsStringNotUtf8)?,
+        path.to_str()
+            .ok_or_else(|| ProjectError::OsStringNotUtf8(path.clone()))?,
     )?;
-    Ok(ModuleCreator::new(path, line_cnt, par_path).create(stmts))
+    let mut creator = ModuleCreator::new(path, line_cnt, par_path);
+    if let Some(namer) = &options.mod_namer {
+        creator = creator.with_mod_namer(namer.clone());
+    }
+    if let Some(limit) = options.max_body_lines {
+        creator = creator.with_max_body_lines(limit);
+    }
+    if let Some(root) = &options.source_root {
+        creator = creator.with_source_root(root.clone());
+    }
+    Ok(creator.create(stmts))
+}
+
+/// Whether the rendered base reference `base` refers to `name`,
+/// allowing either side to carry a dotted prefix the other lacks.
+fn base_matches(base: &str, name: &str) -> bool {
+    base == name
+        || base
+            .strip_suffix(name)
+            .is_some_and(|pre| pre.ends_with('.'))
+        || name
+            .strip_suffix(base)
+            .is_some_and(|pre| pre.ends_with('.'))
+}
+
+/// The encoding `path` declares: `"utf-8-sig"` for a UTF-8 BOM, the
+/// PEP 263 coding cookie when one of the first two lines carries one,
+/// `"utf-8"` otherwise.
+fn declared_encoding(path: &Path) -> Result<String> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = BufReader::new(file);
+    let mut first = Vec::new();
+    reader.read_until(b'\n', &mut first)?;
+    if first.starts_with(&[0xef, 0xbb, 0xbf]) {
+        return Ok("utf-8-sig".to_string());
+    }
+    let mut second = Vec::new();
+    reader.read_until(b'\n', &mut second)?;
+    for line in [first, second] {
+        if let Some(enc) = coding_cookie(&String::from_utf8_lossy(&line)) {
+            return Ok(enc);
+        }
+    }
+    Ok("utf-8".to_string())
+}
+
+/// The encoding named by a PEP 263 cookie on `line`
+/// (`# -*- coding: latin-1 -*-` and friends), if it carries one.
+fn coding_cookie(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    if !line.starts_with('#') {
+        return None;
+    }
+    let rest = &line[line.find("coding")? + "coding".len()..];
+    let rest = rest
+        .strip_prefix(':')
+        .or_else(|| rest.strip_prefix('='))?
+        .trim_start();
+    let name: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Whether the first line of `path` is a `#!` line mentioning python,
+/// marking an extension-less file as a Python script.
+fn has_python_shebang(path: &Path) -> bool {
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut line = String::new();
+    if BufReader::new(file).read_line(&mut line).is_err() {
+        return false;
+    }
+    line.starts_with("#!") && line.contains("python")
 }
 
 struct DirChildren {
@@ -92,7 +1005,7 @@ struct DirChildren {
 }
 
 impl DirChildren {
-    fn create(path: &Path) -> Result<Self> {
+    fn create(path: &Path, include_scripts: bool) -> Result<Self> {
         let mut files = Vec::new();
         let mut dirs = Vec::new();
         let mut init = None;
@@ -100,13 +1013,18 @@ impl DirChildren {
         for entry in path.read_dir()? {
             let entry = entry?;
             let name = entry.file_name();
-            let name = name.to_str().ok_or(ProjectError::OsStringNotUtf8)?;
+            let name = name
+                .to_str()
+                .ok_or_else(|| ProjectError::OsStringNotUtf8(entry.path()))?;
             let kind = entry.file_type()?;
             let entry_path = entry.path();
             if kind.is_dir() && name != "__pycache__" {
                 dirs.push(entry_path);
             } else if kind.is_file() {
-                if !name.ends_with(".py") {
+                let is_module = name.ends_with(".py") || name.ends_with(".pyw");
+                let is_script =
+                    include_scripts && !name.contains('.') && has_python_shebang(&entry_path);
+                if !is_module && !is_script {
                     continue;
                 }
                 if name == "__init__.py" {
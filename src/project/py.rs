@@ -4,12 +4,17 @@ use std::{
         HashMap,
     },
     hash::{Hash, Hasher},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use pyo3::{exceptions::PyRuntimeError, prelude::*, pyclass::CompareOp};
+use pyo3::{
+    exceptions::{PyKeyError, PyRuntimeError},
+    prelude::*,
+    pyclass::CompareOp,
+    types::PyDict,
+};
 
-use crate::object::py::module_to_py;
+use crate::object::py::{module_outline_to_py, module_to_py, object_to_py};
 
 #[pyclass(get_all, set_all)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -58,8 +63,10 @@ pub struct ObjectDb {
 
 #[pymethods]
 impl ObjectDb {
-    fn __getitem__(&self, pos: &Position) -> &PyObject {
-        &self.db[pos]
+    fn __getitem__(&self, pos: &Position) -> PyResult<&PyObject> {
+        self.db
+            .get(pos)
+            .ok_or_else(|| PyKeyError::new_err(pos.__str__()))
     }
 
     fn __len__(&self) -> usize {
@@ -73,6 +80,23 @@ impl ObjectDb {
         Py::new(slf.py(), iter)
     }
 
+    /// The object at `pos`, or `default` when the position is unknown,
+    /// mirroring dict `get` semantics.
+    #[pyo3(signature = (pos, default = None))]
+    fn get(&self, pos: &Position, default: Option<PyObject>) -> Option<PyObject> {
+        self.db.get(pos).cloned().or(default)
+    }
+
+    /// Looks up a whole batch of positions at once, returning the
+    /// object or `None` for each, in order. Handy for mapping the
+    /// frames of a stack trace without a try/except per entry.
+    fn get_many(&self, positions: Vec<Position>) -> Vec<Option<PyObject>> {
+        positions
+            .iter()
+            .map(|pos| self.db.get(pos).cloned())
+            .collect()
+    }
+
     // TODO: Implement items()
     // TODO: Implement values()
     // TODO: Implement has_ob()
@@ -102,11 +126,505 @@ impl From<super::ProjectError> for PyErr {
     }
 }
 
+/// The toggles of a parse, mirroring the Rust-side options struct
+/// field for field (minus the module-namer callback, which cannot
+/// cross the boundary). Pass one to `module_from_dir` or
+/// `parse_project` as `options=` instead of spelling out the
+/// individual keyword arguments; when given, it wins over them.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    relative_paths: bool,
+    max_depth: Option<usize>,
+    lenient: bool,
+    keep_skipped: bool,
+    include_scripts: bool,
+    collapse_trivial_packages: bool,
+    max_body_lines: Option<usize>,
+    source_root: Option<String>,
+    python_version: Option<String>,
+}
+
+#[pymethods]
+impl ParseOptions {
+    #[new]
+    #[pyo3(signature = (
+        relative_paths = false, max_depth = None, lenient = false, keep_skipped = false,
+        include_scripts = false, collapse_trivial_packages = false, max_body_lines = None,
+        source_root = None, python_version = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        relative_paths: bool,
+        max_depth: Option<usize>,
+        lenient: bool,
+        keep_skipped: bool,
+        include_scripts: bool,
+        collapse_trivial_packages: bool,
+        max_body_lines: Option<usize>,
+        source_root: Option<String>,
+        python_version: Option<String>,
+    ) -> Self {
+        Self {
+            relative_paths,
+            max_depth,
+            lenient,
+            keep_skipped,
+            include_scripts,
+            collapse_trivial_packages,
+            max_body_lines,
+            source_root,
+            python_version,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl From<ParseOptions> for super::ProjectOptions {
+    fn from(value: ParseOptions) -> Self {
+        Self {
+            relative_paths: value.relative_paths,
+            max_depth: value.max_depth,
+            lenient: value.lenient,
+            keep_skipped: value.keep_skipped,
+            include_scripts: value.include_scripts,
+            collapse_trivial_packages: value.collapse_trivial_packages,
+            max_body_lines: value.max_body_lines,
+            source_root: value.source_root,
+            python_version: value.python_version,
+            ..Default::default()
+        }
+    }
+}
+
 #[pyfunction]
-#[pyo3(signature = (path))]
-pub fn module_from_dir(py: Python, path: String) -> PyResult<&PyAny> {
+#[pyo3(signature = (
+    path, relative_paths = false, max_depth = None, lenient = false, keep_skipped = false,
+    include_scripts = false, collapse_trivial_packages = false, max_body_lines = None,
+    source_root = None, lazy_stmts = false, options = None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn module_from_dir(
+    py: Python,
+    path: String,
+    relative_paths: bool,
+    max_depth: Option<usize>,
+    lenient: bool,
+    keep_skipped: bool,
+    include_scripts: bool,
+    collapse_trivial_packages: bool,
+    max_body_lines: Option<usize>,
+    source_root: Option<String>,
+    lazy_stmts: bool,
+    options: Option<ParseOptions>,
+) -> PyResult<&PyAny> {
     let path = PathBuf::from(path);
-    let project = super::Project::create(path)?;
-    let module = module_to_py(py, project.root_ob)?;
+    let options = match options {
+        Some(options) => options.into(),
+        None => super::ProjectOptions {
+            relative_paths,
+            max_depth,
+            lenient,
+            keep_skipped,
+            include_scripts,
+            collapse_trivial_packages,
+            max_body_lines,
+            source_root,
+            ..Default::default()
+        },
+    };
+    // The parse phase is pure Rust, so the GIL is released for its
+    // duration and an asyncio event loop can keep running; only the
+    // translation back to Python objects holds it.
+    let project = py.allow_threads(|| super::Project::create_with_options(path, options))?;
+    // With `lazy_stmts` the statement maps stay on the Rust side and
+    // are translated to `ast` objects on first attribute access, which
+    // cuts the translation cost when bodies are rarely inspected.
+    let module = module_to_py(py, project.root_ob, lazy_stmts)?;
     Ok(module)
 }
+
+/// The result of [`parse_project`]: the translated module tree
+/// bundled with the diagnostics gathered while building it.
+#[pyclass]
+pub struct ParseResult {
+    /// The root `Module` of the parsed tree.
+    #[pyo3(get)]
+    module: PyObject,
+    /// The errors a lenient scan skipped over, as strings; empty when
+    /// nothing failed (or `lenient` was off, which raises instead).
+    #[pyo3(get)]
+    errors: Vec<String>,
+    /// One warning per module that was skipped or only partially
+    /// parsed and so is missing from — or incomplete in — the tree.
+    #[pyo3(get)]
+    warnings: Vec<String>,
+    /// Counts describing the tree: `modules`, `classes`, `functions`,
+    /// `alt_objects` and `statements`.
+    #[pyo3(get)]
+    stats: PyObject,
+}
+
+#[pymethods]
+impl ParseResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "parse result: {} errors, {} warnings",
+            self.errors.len(),
+            self.warnings.len()
+        )
+    }
+}
+
+/// Parses `path` like `module_from_dir`, but returns a `ParseResult`
+/// carrying the diagnostics alongside the root module: the errors a
+/// lenient scan skipped, one warning per module that was skipped or
+/// partially parsed, and a stats dict. One call gives tool authors
+/// the tree and everything that went wrong building it.
+#[pyfunction]
+#[pyo3(signature = (
+    path, relative_paths = false, max_depth = None, lenient = false, keep_skipped = false,
+    include_scripts = false, collapse_trivial_packages = false, max_body_lines = None,
+    source_root = None, lazy_stmts = false, options = None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_project(
+    py: Python<'_>,
+    path: String,
+    relative_paths: bool,
+    max_depth: Option<usize>,
+    lenient: bool,
+    keep_skipped: bool,
+    include_scripts: bool,
+    collapse_trivial_packages: bool,
+    max_body_lines: Option<usize>,
+    source_root: Option<String>,
+    lazy_stmts: bool,
+    options: Option<ParseOptions>,
+) -> PyResult<ParseResult> {
+    let path = PathBuf::from(path);
+    let options = match options {
+        Some(options) => options.into(),
+        None => super::ProjectOptions {
+            relative_paths,
+            max_depth,
+            lenient,
+            keep_skipped,
+            include_scripts,
+            collapse_trivial_packages,
+            max_body_lines,
+            source_root,
+            ..Default::default()
+        },
+    };
+    let project = py.allow_threads(|| super::Project::create_with_options(path, options))?;
+    let errors = project.errors.iter().map(ToString::to_string).collect();
+    let warnings = parse_warnings(&project.root_ob);
+    let (modules, classes, functions, alt_objects) = project.counts();
+    let stats = PyDict::new(py);
+    stats.set_item("modules", modules)?;
+    stats.set_item("classes", classes)?;
+    stats.set_item("functions", functions)?;
+    stats.set_item("alt_objects", alt_objects)?;
+    stats.set_item("statements", project.total_statements())?;
+    let module = module_to_py(py, project.root_ob, lazy_stmts)?;
+    Ok(ParseResult {
+        module: module.into_py(py),
+        errors,
+        warnings,
+        stats: stats.into_py(py),
+    })
+}
+
+/// One warning per module below (and including) `module` whose parse
+/// status is not `"ok"`.
+fn parse_warnings(module: &crate::object::Module) -> Vec<String> {
+    let mut modules = vec![module];
+    modules.extend(module.all_submodules());
+    let mut warnings = Vec::new();
+    for m in modules {
+        match m.parse_status() {
+            crate::object::ParseStatus::Ok => {}
+            crate::object::ParseStatus::Partial => {
+                warnings.push(format!("module {} was only partially parsed", m.path()));
+            }
+            crate::object::ParseStatus::Skipped => {
+                warnings.push(format!(
+                    "module {} failed to parse and is an empty placeholder",
+                    m.path()
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Parses exactly the files listed in the manifest at `path` — one
+/// file path per line, relative to the manifest's directory — and
+/// returns the module tree. Parent packages whose `__init__.py` is not
+/// listed become empty placeholder modules, keeping the tree's shape.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn module_from_manifest(py: Python<'_>, path: String) -> PyResult<&PyAny> {
+    let project = py.allow_threads(|| super::Project::from_manifest(&PathBuf::from(path)))?;
+    module_to_py(py, project.root_ob, false)
+}
+
+/// Parses `path` and returns the object tree as a JSON string, in the
+/// shape of the `to_dict` methods, without ever constructing the
+/// intermediate Python objects. For large projects this is much
+/// cheaper than `module_from_dir` followed by `json.dumps`.
+#[cfg(feature = "serde")]
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn project_json(py: Python<'_>, path: String) -> PyResult<String> {
+    py.allow_threads(|| {
+        let project = super::Project::create(PathBuf::from(path))?;
+        Ok(project.to_json())
+    })
+}
+
+/// Parses `path` and returns a lightweight outline of the tree: nested
+/// dicts holding only `name`, `kind`, `span` and `children`. Function
+/// bodies and arguments are never translated, so this is dramatically
+/// faster than `module_from_dir` when only the shape is needed, as in
+/// editor autocomplete.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn module_outline(py: Python<'_>, path: String) -> PyResult<&PyDict> {
+    let project = py.allow_threads(|| super::Project::create(PathBuf::from(path)))?;
+    module_outline_to_py(py, &project.root_ob)
+}
+
+/// Counts the objects under `path` by kind, as a dict with the keys
+/// `modules`, `classes`, `functions` and `alt_objects`.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn project_counts(py: Python<'_>, path: String) -> PyResult<&PyDict> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    let (modules, classes, functions, alt_objects) = project.counts();
+    let dict = PyDict::new(py);
+    dict.set_item("modules", modules)?;
+    dict.set_item("classes", classes)?;
+    dict.set_item("functions", functions)?;
+    dict.set_item("alt_objects", alt_objects)?;
+    Ok(dict)
+}
+
+/// Lists the files a parse of `path` would include, without parsing
+/// anything: a dry run of the directory traversal.
+#[pyfunction]
+#[pyo3(signature = (path, max_depth = None, lenient = false, include_scripts = false))]
+pub fn list_files(
+    path: String,
+    max_depth: Option<usize>,
+    lenient: bool,
+    include_scripts: bool,
+) -> PyResult<Vec<PathBuf>> {
+    let options = super::ProjectOptions {
+        max_depth,
+        lenient,
+        include_scripts,
+        ..Default::default()
+    };
+    Ok(super::Project::list_files(&PathBuf::from(path), &options)?)
+}
+
+/// The declared encoding of every file a default parse of `path` would
+/// include, as `(path, encoding)` pairs: `"utf-8-sig"` for a UTF-8
+/// BOM, the PEP 263 coding cookie when present, `"utf-8"` otherwise.
+/// Nothing is decoded or parsed.
+#[pyfunction]
+pub fn detect_encodings(path: String) -> PyResult<Vec<(PathBuf, String)>> {
+    Ok(super::Project::detect_encodings(&PathBuf::from(path))?)
+}
+
+/// The total number of flattened statements the object model retains
+/// for `path`: the summed sizes of every function's statement map.
+/// Module-level statements are not kept by the model and do not count.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn total_statements(path: String) -> PyResult<usize> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project.total_statements())
+}
+
+/// Groups structurally identical functions under `path`, returning the
+/// dotted object paths of each group of clones.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn duplicate_functions(path: String) -> PyResult<Vec<Vec<String>>> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project
+        .find_duplicate_functions()
+        .into_iter()
+        .map(|group| group.into_iter().map(|p| p.to_string()).collect())
+        .collect())
+}
+
+/// The project-local import edges under `path`, as
+/// `(importer, imported)` pairs of dotted module paths, deduplicated
+/// and sorted. External imports are excluded.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn import_graph(path: String) -> PyResult<Vec<(String, String)>> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project
+        .import_graph()
+        .into_iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect())
+}
+
+/// The import cycles among project-local modules under `path`: each
+/// cycle as the sorted dotted paths of its members. An empty list
+/// means imports are acyclic at load time.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn circular_imports(path: String) -> PyResult<Vec<Vec<String>>> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project
+        .circular_imports()
+        .into_iter()
+        .map(|cycle| cycle.into_iter().map(|p| p.to_string()).collect())
+        .collect())
+}
+
+/// Every object under `path` whose span points into `file`, as
+/// translated objects in tree order: the inverse of the tree view,
+/// for building per-file outlines.
+#[pyfunction]
+#[pyo3(signature = (path, file))]
+pub fn objects_in_file(py: Python<'_>, path: String, file: String) -> PyResult<Vec<PyObject>> {
+    let project = py.allow_threads(|| super::Project::create(PathBuf::from(path)))?;
+    project
+        .objects_in_file(Path::new(&file))
+        .into_iter()
+        .map(|ob| object_to_py(py, ob.clone(), false).map(|ob| ob.into_py(py)))
+        .collect()
+}
+
+/// One flat map from fully-qualified dotted path to translated
+/// object, as built by [`symbol_table`]. Alternate definitions are
+/// addressable by their `name#N` keys.
+#[pyclass]
+pub struct SymbolTable {
+    table: HashMap<String, PyObject>,
+}
+
+#[pymethods]
+impl SymbolTable {
+    fn __getitem__(&self, path: &str) -> PyResult<&PyObject> {
+        self.table
+            .get(path)
+            .ok_or_else(|| PyKeyError::new_err(path.to_string()))
+    }
+
+    fn __contains__(&self, path: &str) -> bool {
+        self.table.contains_key(path)
+    }
+
+    fn __len__(&self) -> usize {
+        self.table.len()
+    }
+
+    /// The object at `path`, or `default` when the path is unknown,
+    /// mirroring dict `get` semantics.
+    #[pyo3(signature = (path, default = None))]
+    fn get(&self, path: &str, default: Option<PyObject>) -> Option<PyObject> {
+        self.table.get(path).cloned().or(default)
+    }
+
+    /// Every dotted path in the table, sorted.
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.table.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// Builds a project-wide symbol table for `path` in one walk: a flat
+/// map from fully-qualified dotted path to object, for fast global
+/// lookups without descending the tree. The root module itself is not
+/// in the map.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn symbol_table(py: Python<'_>, path: String) -> PyResult<SymbolTable> {
+    let project = py.allow_threads(|| super::Project::create(PathBuf::from(path)))?;
+    let table = project
+        .symbol_table()
+        .into_iter()
+        .map(|(key, ob)| object_to_py(py, ob.clone(), false).map(|ob| (key, ob.into_py(py))))
+        .collect::<PyResult<HashMap<String, PyObject>>>()?;
+    Ok(SymbolTable { table })
+}
+
+/// Parses `path` and returns the module tree pruned down to the
+/// objects at the dotted `paths`, everything below them, and their
+/// ancestors (kept so the tree stays navigable): a focused view of
+/// e.g. just the objects a PR touches.
+#[pyfunction]
+#[pyo3(signature = (path, paths))]
+pub fn module_subtree(py: Python<'_>, path: String, paths: Vec<String>) -> PyResult<&PyAny> {
+    let project = py.allow_threads(|| super::Project::create(PathBuf::from(path)))?;
+    let paths: Vec<crate::object::ObjectPath> = paths
+        .iter()
+        .map(|p| crate::object::ObjectPath::new(p.split('.').map(String::from).collect()))
+        .collect();
+    module_to_py(py, project.root_ob.subtree(&paths), false)
+}
+
+/// The module/class/function hierarchy under `path` as a GraphViz DOT
+/// string: nodes labeled by name and kind, colored by kind, with
+/// containment edges. Write it to a file and run `dot -Tpng` on it.
+#[pyfunction]
+#[pyo3(signature = (path))]
+pub fn project_dot(path: String) -> PyResult<String> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project.to_dot())
+}
+
+/// The dotted paths of every test function under `path`, by the
+/// conventions of `Function.is_test` with the given prefix and
+/// decorator markers.
+#[pyfunction]
+#[pyo3(signature = (
+    path, prefix = "test_".to_string(), markers = vec!["pytest.mark".to_string()]
+))]
+pub fn test_functions(path: String, prefix: String, markers: Vec<String>) -> PyResult<Vec<String>> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    let markers: Vec<&str> = markers.iter().map(String::as_str).collect();
+    Ok(project
+        .test_functions(&prefix, &markers)
+        .into_iter()
+        .map(|p| p.to_string())
+        .collect())
+}
+
+/// The dotted paths of every class under `path` inheriting from
+/// `base_name`, directly or through other project classes. Matches
+/// both simple (`Model`) and dotted (`models.Model`) base references.
+#[pyfunction]
+#[pyo3(signature = (path, base_name))]
+pub fn subclasses_of(path: String, base_name: String) -> PyResult<Vec<String>> {
+    let project = super::Project::create(PathBuf::from(path))?;
+    Ok(project
+        .subclasses_of(&base_name)
+        .into_iter()
+        .map(|p| p.to_string())
+        .collect())
+}
+
+#[pyfunction]
+#[pyo3(signature = (paths))]
+pub fn modules_from_dirs(py: Python<'_>, paths: Vec<String>) -> PyResult<Vec<&PyAny>> {
+    let roots = paths.into_iter().map(PathBuf::from).collect();
+    let modules = super::Project::create_multi(roots)?;
+    modules
+        .into_iter()
+        .map(|m| module_to_py(py, m, false))
+        .collect()
+}